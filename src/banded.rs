@@ -0,0 +1,189 @@
+use crate::{
+    matrix::{Matrix, MatrixError},
+    number::{NumNonRef, NumRef},
+};
+
+#[derive(Clone, Debug)]
+pub struct BandedMatrix<T>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    // row `i` keeps the columns `i - lower..=i + upper`
+    elems: Vec<T>,
+    size: usize,
+    lower: usize,
+    upper: usize,
+}
+
+impl<T> BandedMatrix<T>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    #[allow(dead_code)]
+    pub fn new(size: usize, lower: usize, upper: usize) -> Self {
+        Self {
+            elems: vec![0.0.into(); size * (lower + upper + 1)],
+            size,
+            lower,
+            upper,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn from_matrix(mat: &Matrix<T>, lower: usize, upper: usize) -> Result<Self, MatrixError> {
+        mat.as_square()?;
+
+        let zero: T = 0.0.into();
+        let mut res = Self::new(mat.width(), lower, upper);
+        for i in 0..res.size {
+            for j in 0..res.size {
+                if res.in_band(i, j) {
+                    res.set(i, j, mat.get(i, j).clone());
+                } else if mat.get(i, j) != &zero {
+                    return Err(MatrixError::NotBanded);
+                }
+            }
+        }
+
+        Ok(res)
+    }
+
+    #[allow(dead_code)]
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let mut res = Matrix::new(self.size, self.size);
+        for i in 0..self.size {
+            for j in self.band_columns(i) {
+                res.set(i, j, self.get(i, j));
+            }
+        }
+        res
+    }
+
+    #[inline(always)]
+    #[allow(dead_code)]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    #[inline(always)]
+    #[allow(dead_code)]
+    pub fn lower(&self) -> usize {
+        self.lower
+    }
+    #[inline(always)]
+    #[allow(dead_code)]
+    pub fn upper(&self) -> usize {
+        self.upper
+    }
+
+    /// Entries outside the band are zero.
+    #[allow(dead_code)]
+    pub fn get(&self, row: usize, column: usize) -> T {
+        assert!(row < self.size && column < self.size);
+        if self.in_band(row, column) {
+            self.elems[self.index(row, column)].clone()
+        } else {
+            0.0.into()
+        }
+    }
+
+    /// Panics when asked to store a nonzero value outside the band.
+    #[allow(dead_code)]
+    pub fn set(&mut self, row: usize, column: usize, val: T) {
+        assert!(row < self.size && column < self.size);
+        if self.in_band(row, column) {
+            let index = self.index(row, column);
+            self.elems[index] = val;
+        } else {
+            assert!(
+                val == 0.0.into(),
+                "({}, {}) is outside the band",
+                row,
+                column
+            );
+        }
+    }
+
+    fn in_band(&self, row: usize, column: usize) -> bool {
+        column + self.lower >= row && column <= row + self.upper
+    }
+
+    fn index(&self, row: usize, column: usize) -> usize {
+        row * (self.lower + self.upper + 1) + column + self.lower - row
+    }
+
+    fn band_columns(&self, row: usize) -> std::ops::Range<usize> {
+        row.saturating_sub(self.lower)..(row + self.upper + 1).min(self.size)
+    }
+
+    /// LU decomposition without pivoting. `L` keeps the lower bandwidth and `U`
+    /// the upper one, so the whole thing is O(n * lower * upper).
+    #[allow(dead_code)]
+    pub fn lu(&self) -> Result<(Self, Self), MatrixError> {
+        let zero: T = 0.0.into();
+        let n = self.size;
+        let mut d = self.clone();
+
+        for k in 0..n {
+            let pivot = d.get(k, k);
+            if pivot == zero {
+                return Err(MatrixError::NotRegular);
+            }
+
+            for i in k + 1..(k + self.lower + 1).min(n) {
+                let m = &d.get(i, k) / &pivot;
+                for j in k + 1..(k + self.upper + 1).min(n) {
+                    let v = d.get(i, j) - &m * &d.get(k, j);
+                    d.set(i, j, v);
+                }
+                d.set(i, k, m);
+            }
+        }
+
+        let mut l = Self::new(n, self.lower, 0);
+        let mut u = Self::new(n, 0, self.upper);
+        for i in 0..n {
+            for j in d.band_columns(i) {
+                if j < i {
+                    l.set(i, j, d.get(i, j));
+                } else {
+                    u.set(i, j, d.get(i, j));
+                }
+            }
+            l.set(i, i, 1.0.into());
+        }
+
+        Ok((l, u))
+    }
+
+    #[allow(dead_code)]
+    pub fn solve(&self, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        if b.width() != 1 || b.height() != self.size {
+            return Err(MatrixError::SizeMismatch);
+        }
+
+        let (l, u) = self.lu()?;
+        let n = self.size;
+
+        let mut y = Matrix::new(1, n);
+        for i in 0..n {
+            let mut yi = b.get(i, 0).clone();
+            for j in i.saturating_sub(l.lower)..i {
+                yi = yi - &l.get(i, j) * y.get(j, 0);
+            }
+            y.set(i, 0, yi);
+        }
+
+        let mut x = Matrix::new(1, n);
+        for i in (0..n).rev() {
+            let mut xi = y.get(i, 0).clone();
+            for j in i + 1..(i + u.upper + 1).min(n) {
+                xi = xi - &u.get(i, j) * x.get(j, 0);
+            }
+            x.set(i, 0, &xi / &u.get(i, i));
+        }
+
+        Ok(x)
+    }
+}
@@ -1,6 +1,8 @@
 use std::{
-    fmt::Display,
+    fmt::{Alignment, Display},
+    num::ParseFloatError,
     ops::{Add, Div, Mul, Neg, Sub},
+    str::FromStr,
 };
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -28,6 +30,51 @@ impl Complex {
     pub fn abs_squared(&self) -> f32 {
         self.re * self.re + self.im * self.im
     }
+
+    /// `self / rhs`, or `None` when `|rhs|²` is zero, including when it
+    /// underflows. Plain division gives NaN components in that case.
+    pub fn checked_div(self, rhs: Complex) -> Option<Complex> {
+        if rhs.abs_squared() == 0.0 {
+            None
+        } else {
+            Some(self / rhs)
+        }
+    }
+
+    /// Always `re±imi` with both parts written out, which `FromStr` reads back
+    /// to the same bits.
+    pub fn to_parseable_string(self) -> String {
+        let sign = if self.im.is_sign_negative() { '-' } else { '+' };
+        format!("{}{}{}i", self.re, sign, self.im.abs())
+    }
+}
+
+impl FromStr for Complex {
+    type Err = ParseFloatError;
+
+    // accepts `a`, `bi`, `a+bi`, `a-bi`, with `i`/`-i` for a unit imaginary part
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let Some(im_part) = s.strip_suffix('i') else {
+            return Ok(Self::from(s.parse::<f32>()?));
+        };
+
+        let bytes = im_part.as_bytes();
+        let split = (1..bytes.len()).rev().find(|&i| {
+            (bytes[i] == b'+' || bytes[i] == b'-') && !matches!(bytes[i - 1], b'e' | b'E')
+        });
+        let (re, im) = match split {
+            Some(i) => (im_part[..i].parse()?, &im_part[i..]),
+            None => (0.0, im_part),
+        };
+        let im = match im {
+            "" | "+" => 1.0,
+            "-" => -1.0,
+            im => im.parse()?,
+        };
+
+        Ok(Self { re, im })
+    }
 }
 
 impl From<f32> for Complex {
@@ -72,7 +119,8 @@ impl Mul for Complex {
 impl Div for Complex {
     type Output = Self;
 
-    // (a + ib) / (c + id) = (a + ib) * (c - id) / (c^2 + d^2)
+    // (a + ib) / (c + id) = (a + ib) * (c - id) / (c^2 + d^2), which is
+    // NaN in both parts for a zero `rhs`, see `checked_div`
     fn div(self, rhs: Self) -> Self::Output {
         let mul = self * rhs.conjugate();
         let abs = rhs.abs_squared();
@@ -247,22 +295,36 @@ impl Div<&Complex> for Complex {
 }
 
 impl Display for Complex {
+    // the precision applies to both parts, the width and alignment to the
+    // whole `a+bi` string
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.re == 0.0 {
+        let part = |x: f32| match f.precision() {
+            Some(p) => format!("{:.*}", p, x),
+            None => format!("{}", x),
+        };
+
+        let s = if self.re == 0.0 {
             if self.im == 0.0 {
-                write!(f, "0")
+                part(0.0)
             } else {
-                write!(f, "{}i", self.im)
+                format!("{}i", part(self.im))
             }
+        } else if self.im == 0.0 {
+            part(self.re)
         } else {
-            if self.im == 0.0 {
-                write!(f, "{}", self.re)
-            } else if self.im > 0.0 {
-                write!(f, "{}+{}i", self.re, self.im)
-            } else {
-                write!(f, "{}{}i", self.re, self.im)
-            }
-        }
+            let sign = if self.im.is_sign_negative() { '-' } else { '+' };
+            format!("{}{}{}i", part(self.re), sign, part(self.im.abs()))
+        };
+
+        let len = s.chars().count();
+        let pad = f.width().map_or(0, |w| w.saturating_sub(len));
+        let (left, right) = match f.align() {
+            Some(Alignment::Left) => (0, pad),
+            Some(Alignment::Center) => (pad / 2, pad - pad / 2),
+            _ => (pad, 0),
+        };
+        let fill = f.fill().to_string();
+        write!(f, "{}{}{}", fill.repeat(left), s, fill.repeat(right))
     }
 }
 
@@ -1,24 +1,76 @@
 use std::{fmt::Debug, fs::File, io::Write, path::PathBuf, time::Instant};
 
 use crate::{
-    io::read_mat,
+    complex::Complex,
+    io::{read_mat, write_poly_complex, Either},
     longint::LongInt,
+    lu::LuFactorization,
     matrix::{Matrix, MatrixError},
     measure,
     number::{from_f32_mat, NumNonRef, NumRef},
     poly::Polynome,
+    qr::qr_householder,
 };
 
+/// Coefficients this long mean the input was far bigger than intended.
+const FIND_POLY_MAX_DIGITS: usize = 10_000;
+/// Largest `|a_ij|²` outside the three diagonals that still counts as zero.
+const TRIDIAGONAL_TOLERANCE: f32 = 0.0001;
+
 pub fn characteristic_polynomial<T>(mat: &Matrix<T>) -> Result<Polynome<T>, MatrixError>
 where
     T: NumNonRef + Debug,
     for<'a> &'a T: NumRef<T>,
 {
-    if mat.width() != mat.height() {
-        return Err(MatrixError::NotSquare);
-    }
+    characteristic_polynomial_with_tol(mat, TRIDIAGONAL_TOLERANCE)
+}
+
+/// Same as [`characteristic_polynomial`], but entries outside the three
+/// diagonals only have to satisfy `|a_ij|² <= tol`. Those entries are ignored
+/// when building the polynomial.
+#[allow(dead_code)]
+pub fn characteristic_polynomial_with_tol<T>(
+    mat: &Matrix<T>,
+    tol: f32,
+) -> Result<Polynome<T>, MatrixError>
+where
+    T: NumNonRef + Debug,
+    for<'a> &'a T: NumRef<T>,
+{
+    tridiagonal_polynomial(mat, tol, |_| Ok(()))
+}
+
+/// [`characteristic_polynomial`] over `LongInt` that gives up with
+/// `TooManyDigits` as soon as an intermediate coefficient grows past
+/// `max_digits` decimal digits, instead of grinding on huge numbers.
+pub fn characteristic_polynomial_max_digits(
+    mat: &Matrix<LongInt>,
+    max_digits: usize,
+) -> Result<Polynome<LongInt>, MatrixError> {
+    tridiagonal_polynomial(mat, TRIDIAGONAL_TOLERANCE, |p| {
+        if (0..=p.degree()).any(|k| p.get(k).decimal_digits() > max_digits) {
+            Err(MatrixError::TooManyDigits { max_digits })
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// Expands the determinant of `A - λI` along the three diagonals, passing
+/// every intermediate polynomial to `check`.
+fn tridiagonal_polynomial<T, F>(
+    mat: &Matrix<T>,
+    tol: f32,
+    check: F,
+) -> Result<Polynome<T>, MatrixError>
+where
+    T: NumNonRef + Debug,
+    for<'a> &'a T: NumRef<T>,
+    F: Fn(&Polynome<T>) -> Result<(), MatrixError>,
+{
+    mat.as_square()?;
 
-    if !is_tridiagonal(mat, 0.0001) {
+    if !is_tridiagonal(mat, tol) {
         return Err(MatrixError::NotTridiagonal);
     }
 
@@ -49,12 +101,14 @@ where
                 (d + a) * T::from(-1.0),
                 1.0.into(),
             ]));
+            check(&p[1])?;
 
             for i in 2..width {
                 let p1 = Polynome::from_coefs(&[mat.get(i, i).clone(), (-1.0).into()]);
                 let p2 = mat.get(i, i - 1) * mat.get(i - 1, i);
 
                 let p3 = &p[i - 1] * p1 - &p[i - 2] * p2;
+                check(&p3)?;
 
                 p.push(p3);
             }
@@ -64,35 +118,291 @@ where
     }
 }
 
-fn is_tridiagonal<T>(mat: &Matrix<T>, close_enough_to_zero: f32) -> bool
+pub fn is_tridiagonal<T>(mat: &Matrix<T>, close_enough_to_zero: f32) -> bool
 where
     T: NumNonRef,
     for<'a> &'a T: NumRef<T>,
 {
-    for i in 0..mat.height() {
-        for j in 0..mat.width() {
-            if i == j || (j > 0 && j - 1 == i) || (i > 0 && i - 1 == j) {
-                continue;
+    // `close_enough_to_zero` bounds the squared magnitude
+    let (lower, upper) = mat.bandwidth(close_enough_to_zero.sqrt());
+    lower <= 1 && upper <= 1
+}
+
+/// Finds the eigenvalue of `mat` closest to `shift`, together with its unit
+/// eigenvector, by repeatedly solving `(A - shift·I) w = v`.
+#[allow(dead_code)]
+pub fn inverse_power_iteration(
+    mat: &Matrix<f32>,
+    shift: f32,
+    max_iters: usize,
+    epsilon: f32,
+) -> Result<(f32, Matrix<f32>), MatrixError> {
+    mat.as_square()?;
+
+    let mut shifted = mat.clone();
+    shifted.shift_diagonal(&shift);
+    let lu = LuFactorization::new(&shifted)?;
+
+    let mut v = Matrix::from_vec((0..mat.width()).map(|i| (i + 1) as f32).collect(), 1)?;
+    v = &v / v.norm();
+    let mut eigenvalue = shift;
+    let mut change = f32::INFINITY;
+
+    for _ in 0..max_iters {
+        let w = lu.solve(&v)?;
+        v = &w / w.norm();
+
+        let next = v.dot(&mat.mul_vec(&v)?)?;
+        change = (next - eigenvalue).abs();
+        eigenvalue = next;
+        if change < epsilon {
+            return Ok((eigenvalue, v));
+        }
+    }
+
+    Err(MatrixError::DidNotConverge {
+        iterations: max_iters,
+        residual: change,
+    })
+}
+
+/// Eigenvalues of a real square matrix by the shifted QR algorithm.
+///
+/// Every step subtracts the Wilkinson shift `μ` (the eigenvalue of the trailing
+/// 2x2 block closest to the last diagonal entry), factors the active block with
+/// Householder QR and recombines it as `RQ + μI`. Once the last row (or the last
+/// two rows, for a complex conjugate pair) decouples from the rest, those
+/// eigenvalues are taken and the active block shrinks. Running out of
+/// `max_iters` QR steps before that is a `DidNotConverge` error.
+#[allow(dead_code)]
+pub fn eigenvalues(
+    mat: &Matrix<f32>,
+    max_iters: usize,
+    epsilon: f32,
+) -> Result<Vec<Complex>, MatrixError> {
+    mat.as_square()?;
+
+    let tolerance = epsilon * mat.norm();
+    let mut h = mat.clone();
+    let mut values = Vec::with_capacity(mat.width());
+    let mut iters = 0;
+
+    loop {
+        let n = h.width();
+        match n {
+            0 => break,
+            1 => {
+                values.push(Complex::from(*h.get(0, 0)));
+                break;
+            }
+            2 => {
+                values.extend(block_eigenvalues(&h, 0));
+                break;
+            }
+            _ => {}
+        }
+
+        if coupling_norm(&h, n - 1) <= tolerance {
+            values.push(Complex::from(*h.get(n - 1, n - 1)));
+            h = leading_block(&h, n - 1);
+            continue;
+        }
+        if coupling_norm(&h, n - 2) <= tolerance {
+            values.extend(block_eigenvalues(&h, n - 2));
+            h = leading_block(&h, n - 2);
+            continue;
+        }
+
+        if iters == max_iters {
+            return Err(MatrixError::DidNotConverge {
+                iterations: max_iters,
+                residual: coupling_norm(&h, n - 1),
+            });
+        }
+        iters += 1;
+
+        let shift = wilkinson_shift(&h);
+        h.shift_diagonal(&shift);
+        let (q, r) = qr_householder(&h)?;
+        h = (r * q)?;
+        h.shift_diagonal(&-shift);
+    }
+
+    Ok(values)
+}
+
+/// Unit eigenvectors for the real entries of `eigenvalues`, in the same order.
+///
+/// Each vector spans the null space of `A - λI`: the shifted matrix is factored
+/// with QR, the column with the smallest `|R_kk|` is taken as the free variable
+/// and the rest of the vector is found by back substitution. Complex eigenvalues
+/// are skipped for now, so the result may be shorter than `eigenvalues`.
+#[allow(dead_code)]
+pub fn eigenvectors(
+    mat: &Matrix<f32>,
+    eigenvalues: &[Complex],
+) -> Result<Vec<Matrix<f32>>, MatrixError> {
+    mat.as_square()?;
+
+    let n = mat.width();
+    let mut vectors = Vec::new();
+    for lambda in eigenvalues.iter().filter(|l| l.im == 0.0) {
+        let mut shifted = mat.clone();
+        shifted.shift_diagonal(&lambda.re);
+        let (_, r) = qr_householder(&shifted)?;
+
+        let k = (0..n)
+            .min_by(|&i, &j| r.get(i, i).abs().total_cmp(&r.get(j, j).abs()))
+            .unwrap_or(0);
+
+        let mut v = Matrix::new(1, n);
+        v.set(k, 0, 1.0);
+        if k > 0 {
+            let mut rhs = Matrix::new(1, k);
+            for i in 0..k {
+                rhs.set(i, 0, -r.get(i, k));
             }
-            if mat.get(i, j).norm_squared() > close_enough_to_zero {
-                return false;
+            let x = leading_block(&r, k).solve_upper_triangular(&rhs)?;
+            for i in 0..k {
+                v.set(i, 0, *x.get(i, 0));
             }
         }
+
+        let norm = v.norm();
+        vectors.push(v / norm);
+    }
+
+    Ok(vectors)
+}
+
+// how strongly rows `from..` are still coupled to the columns before them
+fn coupling_norm(h: &Matrix<f32>, from: usize) -> f32 {
+    let mut sum = 0.0;
+    for i in from..h.height() {
+        for j in 0..from {
+            sum += h.get(i, j) * h.get(i, j);
+        }
+    }
+    sum.sqrt()
+}
+
+fn leading_block(h: &Matrix<f32>, n: usize) -> Matrix<f32> {
+    let mut res = Matrix::new(n, n);
+    for i in 0..n {
+        for j in 0..n {
+            res.set(i, j, *h.get(i, j));
+        }
     }
-    return true;
+    res
 }
 
-pub fn find_poly(dir: &PathBuf, problem: usize) -> Result<(), MatrixError> {
+fn block_eigenvalues(h: &Matrix<f32>, k: usize) -> [Complex; 2] {
+    let (a, b) = (*h.get(k, k), *h.get(k, k + 1));
+    let (c, d) = (*h.get(k + 1, k), *h.get(k + 1, k + 1));
+
+    let half_trace = (a + d) / 2.0;
+    let disc = (a - d) * (a - d) / 4.0 + b * c;
+    if disc >= 0.0 {
+        [
+            Complex::from(half_trace + disc.sqrt()),
+            Complex::from(half_trace - disc.sqrt()),
+        ]
+    } else {
+        [
+            Complex::new(half_trace, (-disc).sqrt()),
+            Complex::new(half_trace, -(-disc).sqrt()),
+        ]
+    }
+}
+
+fn wilkinson_shift(h: &Matrix<f32>) -> f32 {
+    let n = h.width();
+    let (a, b) = (*h.get(n - 2, n - 2), *h.get(n - 2, n - 1));
+    let (c, d) = (*h.get(n - 1, n - 2), *h.get(n - 1, n - 1));
+
+    let delta = (a - d) / 2.0;
+    let disc = delta * delta + b * c;
+    if disc < 0.0 {
+        // complex pair, no real eigenvalue to aim at
+        return d;
+    }
+
+    let denom = delta
+        + if delta >= 0.0 {
+            disc.sqrt()
+        } else {
+            -disc.sqrt()
+        };
+    if denom == 0.0 {
+        d
+    } else {
+        d - b * c / denom
+    }
+}
+
+/// Writes the characteristic polynomial of `Amat{problem}.m` to
+/// `cvec{problem}.m`. If `evec{problem}.m` holds candidate eigenvalues, their
+/// residuals `|p(λ)|` are printed and returned.
+pub fn find_poly(dir: &PathBuf, problem: usize) -> Result<Option<Vec<f32>>, MatrixError> {
     let mat_file = dir.join(format!("Amat{problem}.m"));
+    let poly_file = dir.join(format!("cvec{problem}.m"));
+    let candidates_file = dir.join(format!("evec{problem}.m"));
     println!("Problem {problem}");
-    let m = read_mat(&mut File::open(&mat_file)?)?
-        .0
-        .unwrap_left()
-        .clone();
-    let m = from_f32_mat::<LongInt>(&m);
-    let (p, duration) = measure!(characteristic_polynomial(&m)?);
-    println!("\tTook {}μs", duration.as_micros());
-    write!(File::create(dir.join(format!("cvec{problem}.m")))?, "{}", p)?;
-
-    Ok(())
+
+    let residuals = match read_mat(&mut File::open(&mat_file)?)?.0 {
+        Either::Left(m) => {
+            let m = from_f32_mat::<LongInt>(&m);
+            let (p, duration) = measure!(characteristic_polynomial_max_digits(
+                &m,
+                FIND_POLY_MAX_DIGITS
+            )?);
+            println!("\tTook {}μs", duration.as_micros());
+            write!(File::create(poly_file)?, "{}", p)?;
+
+            // candidate eigenvalues are optional, if given show how far off they are
+            match File::open(candidates_file) {
+                Ok(mut candidates) => {
+                    let coefs: Vec<f32> = (0..=p.degree()).map(|k| f32::from(&p.get(k))).collect();
+                    Some(match read_mat(&mut candidates)?.0 {
+                        Either::Left(l) => Polynome::from_coefs(&coefs).residuals(l.elems_raw()),
+                        Either::Right(l) => {
+                            let coefs: Vec<Complex> =
+                                coefs.into_iter().map(Complex::from).collect();
+                            Polynome::from_coefs(&coefs).residuals(l.elems_raw())
+                        }
+                    })
+                }
+                Err(_) => None,
+            }
+        }
+        Either::Right(m) => {
+            let (p, duration) = measure!(characteristic_polynomial(&m)?);
+            println!("\tTook {}μs", duration.as_micros());
+            write_poly_complex(&p, &poly_file)?;
+
+            match File::open(candidates_file) {
+                Ok(mut candidates) => {
+                    let points: Vec<Complex> = match read_mat(&mut candidates)?.0 {
+                        Either::Left(l) => {
+                            l.elems_raw().iter().map(|&x| Complex::from(x)).collect()
+                        }
+                        Either::Right(l) => l.elems_raw().to_vec(),
+                    };
+                    Some(p.residuals(&points))
+                }
+                Err(_) => None,
+            }
+        }
+    };
+
+    if let Some(residuals) = &residuals {
+        print_residuals(residuals);
+    }
+    Ok(residuals)
+}
+
+fn print_residuals(residuals: &[f32]) {
+    for (i, r) in residuals.iter().enumerate() {
+        println!("\t|p(λ{})| = {}", i + 1, r);
+    }
 }
@@ -35,6 +35,15 @@ where
         res
     }
 
+    #[inline(always)]
+    pub fn num(&self) -> &T {
+        &self.num
+    }
+    #[inline(always)]
+    pub fn den(&self) -> &T {
+        &self.den
+    }
+
     fn gcd(mut a: T, mut b: T) -> T {
         while b != 0.0.into() {
             let t = b.clone();
@@ -45,7 +54,8 @@ where
     }
 
     fn simplify(&mut self) {
-        let a = self.num.clone();
+        // keep the gcd positive so the sign stays on the numerator
+        let a = self.num.absolute();
         let b = self.den.clone();
 
         let gcd = Self::gcd(a, b);
@@ -295,17 +305,53 @@ where
     T: NumNonRef + PartialOrd,
     for<'a> &'a T: NumRef<T> + Rem<Output = T>,
 {
+    // every finite f32 is m * 2^e with an integer m, so the conversion is exact
     fn from(x: f32) -> Self {
-        let den = T::from(100.0 / x.fract());
-
-        Fraction {
-            num: T::from(x.trunc()),
-            den: T::from(1.0),
-        } + Fraction {
-            num: T::from(100.0),
-            den,
+        assert!(x.is_finite(), "cannot convert {} to a fraction", x);
+        if x == 0.0 {
+            return Fraction {
+                num: 0.0.into(),
+                den: 1.0.into(),
+            };
+        }
+
+        let bits = x.to_bits();
+        let biased_exp = ((bits >> 23) & 0xff) as i32;
+        let mut mantissa = bits & 0x7f_ffff;
+        let exp = if biased_exp == 0 {
+            -149
+        } else {
+            mantissa |= 0x80_0000;
+            biased_exp - 150
+        };
+
+        // the mantissa has at most 24 bits, so it is exact as an f32
+        let num = T::from(mantissa as f32);
+        let num = if x < 0.0 { -num } else { num };
+        let scale = pow2::<T>(exp.unsigned_abs());
+        if exp >= 0 {
+            Fraction::new(1.0.into(), num * scale)
+        } else {
+            Fraction::new(scale, num)
+        }
+    }
+}
+
+fn pow2<T>(mut exp: u32) -> T
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    let mut res: T = 1.0.into();
+    let mut base: T = 2.0.into();
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res = &res * &base;
         }
+        base = &base * &base;
+        exp >>= 1;
     }
+    res
 }
 
 impl<T> Into<f32> for Fraction<T>
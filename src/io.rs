@@ -1,8 +1,14 @@
-use std::{fs::File, io::Read, io::Write, path::PathBuf};
+use std::{
+    fs::File,
+    io::Read,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 use crate::{
     complex::Complex,
     matrix::{Matrix, MatrixError},
+    poly::Polynome,
 };
 
 #[derive(Debug)]
@@ -12,6 +18,11 @@ pub enum Either<L, R> {
 }
 
 impl<L, R> Either<L, R> {
+    pub fn is_left(&self) -> bool {
+        matches!(self, Either::Left(_))
+    }
+
+    #[allow(dead_code)]
     pub fn unwrap_left(&self) -> &L {
         match &self {
             Either::Left(l) => l,
@@ -19,53 +30,161 @@ impl<L, R> Either<L, R> {
         }
     }
 
+    #[allow(dead_code)]
     pub fn unwrap_right(&self) -> &R {
         match &self {
             Either::Left(_) => panic!("unwrap_right(): is left"),
             Either::Right(r) => r,
         }
     }
+
+    #[allow(dead_code)]
+    pub fn map_left<L2, F: FnOnce(L) -> L2>(self, f: F) -> Either<L2, R> {
+        match self {
+            Either::Left(l) => Either::Left(f(l)),
+            Either::Right(r) => Either::Right(r),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn map_right<R2, G: FnOnce(R) -> R2>(self, g: G) -> Either<L, R2> {
+        match self {
+            Either::Left(l) => Either::Left(l),
+            Either::Right(r) => Either::Right(g(r)),
+        }
+    }
+
+    /// Applies `f` or `g`, whichever matches the variant present.
+    #[allow(dead_code)]
+    pub fn map_both<L2, R2, F, G>(self, f: F, g: G) -> Either<L2, R2>
+    where
+        F: FnOnce(L) -> L2,
+        G: FnOnce(R) -> R2,
+    {
+        match self {
+            Either::Left(l) => Either::Left(f(l)),
+            Either::Right(r) => Either::Right(g(r)),
+        }
+    }
 }
 
+/// A matrix as read from a file, which may hold either real or complex data.
+pub type AnyMatrix = Either<Matrix<f32>, Matrix<Complex>>;
+
 pub enum QRMethod {
     Householder,
     Givens,
     GramSchmidt,
 }
 
-fn read_method(s: &str) -> (Option<QRMethod>, &str) {
-    let next = s.trim_start();
-    if next.starts_with("Method=") {
-        let next = &next["Method=".len()..];
-        match next.chars().next() {
-            Some('1') => return (Some(QRMethod::Householder), &next["1".len()..]),
-            Some('2') => return (Some(QRMethod::Givens), &next["2".len()..]),
-            Some('3') => return (Some(QRMethod::GramSchmidt), &next["3".len()..]),
+/// Reorthogonalization threshold for Gram-Schmidt when the file gives no
+/// `Epsilon=` directive.
+pub const DEFAULT_GRAM_SCHMIDT_EPSILON: f32 = 0.1;
 
-            _ => return (None, s),
+/// Directives that may precede the matrix in a file, e.g. `Method=3 Epsilon=0.001`.
+pub struct MatrixHeader {
+    pub method: Option<QRMethod>,
+    pub epsilon: f32,
+}
+
+/// Drops everything from a `%` or `#` to the end of its line, keeping the
+/// line breaks so that rows stay separated.
+fn strip_comments(s: &str) -> String {
+    s.lines()
+        .map(|line| match line.find(['%', '#']) {
+            Some(i) => &line[..i],
+            None => line,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn read_method(s: &str) -> Result<(MatrixHeader, &str), MatrixError> {
+    let mut header = MatrixHeader {
+        method: None,
+        epsilon: DEFAULT_GRAM_SCHMIDT_EPSILON,
+    };
+    let mut s = s;
+
+    loop {
+        let next = s.trim_start();
+        if let Some(next) = next.strip_prefix("Method=") {
+            header.method = match next.chars().next() {
+                Some('1') => Some(QRMethod::Householder),
+                Some('2') => Some(QRMethod::Givens),
+                Some('3') => Some(QRMethod::GramSchmidt),
+
+                _ => return Ok((header, s)),
+            };
+            s = &next["1".len()..];
+        } else if let Some(next) = next.strip_prefix("Epsilon=") {
+            let end = next.find(char::is_whitespace).unwrap_or(next.len());
+            header.epsilon = next[..end]
+                .parse()
+                .map_err(|_| MatrixError::InvalidFileFormat)?;
+            s = &next[end..];
+        } else {
+            return Ok((header, s));
         }
     }
+}
 
-    return (None, s);
+/// Reads the header and the first matrix. Rows of differing lengths are an
+/// error, see [`read_mat_padded`] to accept them.
+pub fn read_mat<T: Read>(reader: &mut T) -> Result<(AnyMatrix, MatrixHeader), MatrixError> {
+    read_mat_with(reader, false)
 }
 
-pub fn read_mat<T: Read>(
+/// Like [`read_mat`], but short rows are padded with zeros up to the longest.
+#[allow(dead_code)]
+pub fn read_mat_padded<T: Read>(reader: &mut T) -> Result<(AnyMatrix, MatrixHeader), MatrixError> {
+    read_mat_with(reader, true)
+}
+
+fn read_mat_with<T: Read>(
     reader: &mut T,
-) -> Result<(Either<Matrix<f32>, Matrix<Complex>>, Option<QRMethod>), MatrixError> {
+    pad_rows: bool,
+) -> Result<(AnyMatrix, MatrixHeader), MatrixError> {
     let mut s = String::new();
     reader.read_to_string(&mut s)?;
-    let s = &s[..];
-    let (method, mut s) = read_method(&s);
-    for (i, c) in s.char_indices() {
-        if c == '[' {
-            s = &s[i..];
-            break;
-        }
+    let s = strip_comments(&s);
+    let (header, s) = read_method(&s)?;
+    if s.trim().is_empty() {
+        return Err(MatrixError::InvalidFileFormat);
+    }
+    let (mat, _) = read_block(s, pad_rows)?;
+
+    Ok((mat, header))
+}
+
+/// Reads every `A = [...];` or `A = complex([...],[...]);` block up to the end
+/// of the input.
+#[allow(dead_code)]
+pub fn read_mats<T: Read>(reader: &mut T) -> Result<Vec<AnyMatrix>, MatrixError> {
+    let mut s = String::new();
+    reader.read_to_string(&mut s)?;
+    let s = strip_comments(&s);
+    let (_, mut s) = read_method(&s)?;
+
+    let mut mats = Vec::new();
+    while s.contains('[') {
+        let (mat, next) = read_block(s, false)?;
+        mats.push(mat);
+        s = next;
     }
 
-    let (m1, s) = read_mat_simple(&s)?;
+    Ok(mats)
+}
+
+fn read_block(s: &str, pad_rows: bool) -> Result<(AnyMatrix, &str), MatrixError> {
+    let s = match s.find('[') {
+        Some(i) => &s[i..],
+        None => return Err(MatrixError::InvalidFileFormat),
+    };
+
+    let (m1, s) = read_mat_simple(s, pad_rows)?;
     if s.starts_with(",") {
-        let (m2, _) = read_mat_simple(&s[",".len()..])?;
+        let (m2, s) = read_mat_simple(&s[",".len()..], pad_rows)?;
 
         if m1.height() != m2.height() || m1.width() != m2.width() {
             return Err(MatrixError::SizeMismatch);
@@ -80,25 +199,23 @@ pub fn read_mat<T: Read>(
                     .collect(),
                 m1.width(),
             )?),
-            method,
+            s,
         ));
     }
 
-    Ok((Either::Left(m1), method))
+    Ok((Either::Left(m1), s))
 }
 
 fn read_float(s: &str) -> Result<(f32, &str), MatrixError> {
-    if !s.starts_with(|c: char| c.is_digit(10)) && !s.starts_with("-") {
+    // `inf` and `NaN` are what `Display` writes for non-finite values
+    if !s.starts_with(|c: char| c.is_ascii_digit() || c == 'i' || c == 'N') && !s.starts_with("-") {
         return Err(MatrixError::InvalidFileFormat);
     }
 
-    let mut end = 0;
-    for (i, c) in s.char_indices() {
-        end = i;
-        if c.is_whitespace() || c == ';' || c == ']' {
-            break;
-        }
-    }
+    // a number running into the end of the input means the `]` is missing
+    let end = s
+        .find(|c: char| c.is_whitespace() || c == ';' || c == ']')
+        .ok_or(MatrixError::InvalidFileFormat)?;
     let x: f32 = match s[..end].parse() {
         Ok(x) => Ok(x),
         Err(e) => Err(MatrixError::IOError(format!("{}", e))),
@@ -106,13 +223,17 @@ fn read_float(s: &str) -> Result<(f32, &str), MatrixError> {
     Ok((x, &s[end..]))
 }
 
-fn read_mat_simple(s: &str) -> Result<(Matrix<f32>, &str), MatrixError> {
+fn read_mat_simple(s: &str, pad_rows: bool) -> Result<(Matrix<f32>, &str), MatrixError> {
     if s.starts_with("[") {
         let mut s = &s["[".len()..];
         let mut v: Vec<Vec<f32>> = Vec::new();
         let mut finished = false;
         let mut max_width = 0;
 
+        if let Some(rest) = s.trim_start().strip_prefix("]") {
+            return Ok((Matrix::new(0, 0), rest));
+        }
+
         while !finished {
             let mut row = Vec::new();
             loop {
@@ -147,6 +268,16 @@ fn read_mat_simple(s: &str) -> Result<(Matrix<f32>, &str), MatrixError> {
             }
         }
 
+        if !pad_rows {
+            if let Some(row) = v.iter().position(|row| row.len() != v[0].len()) {
+                return Err(MatrixError::RaggedRow {
+                    row,
+                    expected: v[0].len(),
+                    found: v[row].len(),
+                });
+            }
+        }
+
         let mut elems = Vec::new();
         for row in &mut v {
             row.resize(max_width, 0.0);
@@ -200,3 +331,246 @@ pub fn write_mat_complex(mat: &Matrix<Complex>, file_path: &PathBuf) -> std::io:
         write_mat_simple(&im)
     )
 }
+
+/// Writes the coefficients of `p` as a `cvec` column, highest power first like
+/// the `Display` of real polynomials, in the `complex(...)` form.
+pub fn write_poly_complex(p: &Polynome<Complex>, file_path: &Path) -> Result<(), MatrixError> {
+    let coefs: Vec<Complex> = (0..=p.degree()).rev().map(|k| p.get(k)).collect();
+    let re = Matrix::from_vec(coefs.iter().map(|z| z.re).collect(), 1)?;
+    let im = Matrix::from_vec(coefs.iter().map(|z| z.im).collect(), 1)?;
+
+    write!(
+        File::create(file_path)?,
+        "cvec = complex({},{});",
+        write_mat_simple(&re),
+        write_mat_simple(&im)
+    )?;
+    Ok(())
+}
+
+/// Writes the eigenvalues as a `lambda` column and the eigenvectors as the
+/// columns of `V`. `lambda` uses the `complex(...)` form if any value has a
+/// nonzero imaginary part.
+#[allow(dead_code)]
+pub fn write_eigen(
+    values: &[Complex],
+    vectors: &[Matrix<f32>],
+    file_path: &Path,
+) -> Result<(), MatrixError> {
+    let re = Matrix::from_vec(values.iter().map(|z| z.re).collect(), 1)?;
+    let lambda = if values.iter().all(|z| z.im == 0.0) {
+        format!("lambda = ...\n{};", write_mat_simple(&re))
+    } else {
+        let im = Matrix::from_vec(values.iter().map(|z| z.im).collect(), 1)?;
+        format!(
+            "lambda = complex({},{});",
+            write_mat_simple(&re),
+            write_mat_simple(&im)
+        )
+    };
+
+    let columns: Vec<_> = vectors.iter().map(|v| v.elems_raw().to_vec()).collect();
+    let v = Matrix::from_columns(&columns)?;
+
+    write!(
+        File::create(file_path)?,
+        "{}\nV = ...\n{};",
+        lambda,
+        write_mat_simple(&v)
+    )?;
+    Ok(())
+}
+
+enum JsonValue {
+    Number(f32),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+fn read_json_string(s: &str) -> Result<(&str, &str), MatrixError> {
+    let s = s
+        .trim_start()
+        .strip_prefix('"')
+        .ok_or(MatrixError::InvalidFileFormat)?;
+    let end = s.find('"').ok_or(MatrixError::InvalidFileFormat)?;
+    Ok((&s[..end], &s[end + 1..]))
+}
+
+fn read_json_value(s: &str) -> Result<(JsonValue, &str), MatrixError> {
+    let s = s.trim_start();
+
+    if let Some(mut s) = s.strip_prefix('[') {
+        let mut items = Vec::new();
+        if let Some(rest) = s.trim_start().strip_prefix(']') {
+            return Ok((JsonValue::Array(items), rest));
+        }
+        loop {
+            let (item, rest) = read_json_value(s)?;
+            items.push(item);
+            let rest = rest.trim_start();
+            if let Some(rest) = rest.strip_prefix(',') {
+                s = rest;
+            } else if let Some(rest) = rest.strip_prefix(']') {
+                return Ok((JsonValue::Array(items), rest));
+            } else {
+                return Err(MatrixError::InvalidFileFormat);
+            }
+        }
+    }
+
+    if let Some(mut s) = s.strip_prefix('{') {
+        let mut fields = Vec::new();
+        if let Some(rest) = s.trim_start().strip_prefix('}') {
+            return Ok((JsonValue::Object(fields), rest));
+        }
+        loop {
+            let (key, rest) = read_json_string(s)?;
+            let rest = rest
+                .trim_start()
+                .strip_prefix(':')
+                .ok_or(MatrixError::InvalidFileFormat)?;
+            let (value, rest) = read_json_value(rest)?;
+            fields.push((key.to_owned(), value));
+            let rest = rest.trim_start();
+            if let Some(rest) = rest.strip_prefix(',') {
+                s = rest;
+            } else if let Some(rest) = rest.strip_prefix('}') {
+                return Ok((JsonValue::Object(fields), rest));
+            } else {
+                return Err(MatrixError::InvalidFileFormat);
+            }
+        }
+    }
+
+    // JSON has no literals for non-finite numbers, `write_json_number` spells
+    // them out as strings
+    if s.starts_with('"') {
+        let (name, rest) = read_json_string(s)?;
+        let x = match name {
+            "NaN" => f32::NAN,
+            "Infinity" => f32::INFINITY,
+            "-Infinity" => f32::NEG_INFINITY,
+            _ => return Err(MatrixError::InvalidFileFormat),
+        };
+        return Ok((JsonValue::Number(x), rest));
+    }
+
+    let end = s
+        .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+        .unwrap_or(s.len());
+    match s[..end].parse() {
+        Ok(x) => Ok((JsonValue::Number(x), &s[end..])),
+        Err(_) => Err(MatrixError::InvalidFileFormat),
+    }
+}
+
+fn write_json_number(x: f32) -> String {
+    if x.is_nan() {
+        "\"NaN\"".to_owned()
+    } else if x.is_infinite() {
+        format!("\"{}Infinity\"", if x < 0.0 { "-" } else { "" })
+    } else {
+        x.to_string()
+    }
+}
+
+fn read_json_matrix(s: &str) -> Result<(usize, usize, Vec<JsonValue>), MatrixError> {
+    let (value, rest) = read_json_value(s)?;
+    if !rest.trim().is_empty() {
+        return Err(MatrixError::InvalidFileFormat);
+    }
+    let JsonValue::Object(fields) = value else {
+        return Err(MatrixError::InvalidFileFormat);
+    };
+
+    let (mut width, mut height, mut data) = (None, None, None);
+    for (key, value) in fields {
+        match (key.as_str(), value) {
+            ("width", JsonValue::Number(w)) if w >= 0.0 && w.fract() == 0.0 => {
+                width = Some(w as usize)
+            }
+            ("height", JsonValue::Number(h)) if h >= 0.0 && h.fract() == 0.0 => {
+                height = Some(h as usize)
+            }
+            ("data", JsonValue::Array(d)) => data = Some(d),
+            _ => return Err(MatrixError::InvalidFileFormat),
+        }
+    }
+
+    match (width, height, data) {
+        (Some(width), Some(height), Some(data)) => match width.checked_mul(height) {
+            Some(len) if len == data.len() => Ok((width, height, data)),
+            Some(_) => Err(MatrixError::SizeMismatch),
+            None => Err(MatrixError::InvalidFileFormat),
+        },
+        _ => Err(MatrixError::InvalidFileFormat),
+    }
+}
+
+impl Matrix<f32> {
+    /// `{"width":w,"height":h,"data":[...]}` with the elements in row-major
+    /// order, non-finite ones as the strings `"NaN"`, `"Infinity"` and
+    /// `"-Infinity"`.
+    #[allow(dead_code)]
+    pub fn to_json(&self) -> String {
+        let data: Vec<_> = self
+            .elems_raw()
+            .iter()
+            .map(|&x| write_json_number(x))
+            .collect();
+        format!(
+            "{{\"width\":{},\"height\":{},\"data\":[{}]}}",
+            self.width(),
+            self.height(),
+            data.join(",")
+        )
+    }
+
+    #[allow(dead_code)]
+    pub fn from_json(s: &str) -> Result<Self, MatrixError> {
+        let (width, height, data) = read_json_matrix(s)?;
+        let mut mat = Matrix::new(width, height);
+        for (i, x) in data.into_iter().enumerate() {
+            match x {
+                JsonValue::Number(x) => mat.set(i / width, i % width, x),
+                _ => return Err(MatrixError::InvalidFileFormat),
+            }
+        }
+        Ok(mat)
+    }
+}
+
+impl Matrix<Complex> {
+    #[allow(dead_code)]
+    pub fn to_json(&self) -> String {
+        let data: Vec<_> = self
+            .elems_raw()
+            .iter()
+            .map(|z| format!("[{},{}]", write_json_number(z.re), write_json_number(z.im)))
+            .collect();
+        format!(
+            "{{\"width\":{},\"height\":{},\"data\":[{}]}}",
+            self.width(),
+            self.height(),
+            data.join(",")
+        )
+    }
+
+    #[allow(dead_code)]
+    pub fn from_json(s: &str) -> Result<Self, MatrixError> {
+        let (width, height, data) = read_json_matrix(s)?;
+        let mut mat = Matrix::new(width, height);
+        for (i, z) in data.into_iter().enumerate() {
+            let z = match z {
+                JsonValue::Number(re) => Complex::from(re),
+                JsonValue::Array(parts) => match parts[..] {
+                    [JsonValue::Number(re), JsonValue::Number(im)] => Complex::new(re, im),
+                    _ => return Err(MatrixError::InvalidFileFormat),
+                },
+                _ => return Err(MatrixError::InvalidFileFormat),
+            };
+            mat.set(i / width, i % width, z);
+        }
+        Ok(mat)
+    }
+}
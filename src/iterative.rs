@@ -0,0 +1,192 @@
+use crate::matrix::{Matrix, MatrixError};
+
+fn check_system(a: &Matrix<f32>, b: &Matrix<f32>) -> Result<(), MatrixError> {
+    a.as_square()?;
+    if b.width() != 1 || b.height() != a.height() {
+        return Err(MatrixError::SizeMismatch);
+    }
+    Ok(())
+}
+
+fn check_diagonal(a: &Matrix<f32>) -> Result<(), MatrixError> {
+    for i in 0..a.width() {
+        if *a.get(i, i) == 0.0 {
+            return Err(MatrixError::NotRegular);
+        }
+    }
+    Ok(())
+}
+
+fn check_converged(residual: f32, tol: f32, iterations: usize) -> Result<(), MatrixError> {
+    if residual <= tol {
+        Ok(())
+    } else {
+        Err(MatrixError::DidNotConverge {
+            iterations,
+            residual,
+        })
+    }
+}
+
+/// Solves `Ax = b` for a symmetric positive definite `A` using only
+/// matrix-vector products, returning `x` and the final residual norm `∥b - Ax∥`
+/// as tracked by the iteration. In exact arithmetic it converges in at most `n`
+/// steps; if the residual is still above `tol` after `max_iters`,
+/// `DidNotConverge` is returned.
+#[allow(dead_code)]
+pub fn conjugate_gradient(
+    a: &Matrix<f32>,
+    b: &Matrix<f32>,
+    max_iters: usize,
+    tol: f32,
+) -> Result<(Matrix<f32>, f32), MatrixError> {
+    check_system(a, b)?;
+
+    let mut x = Matrix::new(1, a.width());
+    let mut r = b.clone();
+    let mut p = r.clone();
+    let mut rr = r.dot(&r)?;
+
+    for _ in 0..max_iters {
+        if rr.sqrt() <= tol {
+            break;
+        }
+
+        let ap = a.mul_vec(&p)?;
+        let alpha = rr / p.dot(&ap)?;
+        x = (x + &p * alpha)?;
+        r = (r - ap * alpha)?;
+
+        let next = r.dot(&r)?;
+        p = (&r + p * (next / rr))?;
+        rr = next;
+    }
+
+    let residual = rr.sqrt();
+    check_converged(residual, tol, max_iters)?;
+    Ok((x, residual))
+}
+
+/// Conjugate gradient with a preconditioner `M⁻¹` applied to the residual
+/// each step, see [`jacobi_preconditioner`]. `preconditioner` must itself be
+/// symmetric positive definite. Returns `x` and the final residual norm, like
+/// [`conjugate_gradient`].
+#[allow(dead_code)]
+pub fn pcg(
+    a: &Matrix<f32>,
+    b: &Matrix<f32>,
+    preconditioner: &Matrix<f32>,
+    max_iters: usize,
+    tol: f32,
+) -> Result<(Matrix<f32>, f32), MatrixError> {
+    check_system(a, b)?;
+    if preconditioner.width() != a.width() || preconditioner.height() != a.height() {
+        return Err(MatrixError::SizeMismatch);
+    }
+
+    let mut x = Matrix::new(1, a.width());
+    let mut r = b.clone();
+    let mut z = preconditioner.mul_vec(&r)?;
+    let mut p = z.clone();
+    let mut rz = r.dot(&z)?;
+
+    for _ in 0..max_iters {
+        if r.norm() <= tol {
+            break;
+        }
+
+        let ap = a.mul_vec(&p)?;
+        let alpha = rz / p.dot(&ap)?;
+        x = (x + &p * alpha)?;
+        r = (r - ap * alpha)?;
+
+        z = preconditioner.mul_vec(&r)?;
+        let next = r.dot(&z)?;
+        p = (&z + p * (next / rz))?;
+        rz = next;
+    }
+
+    let residual = r.norm();
+    check_converged(residual, tol, max_iters)?;
+    Ok((x, residual))
+}
+
+/// `D⁻¹`, the inverse of the diagonal of `a`, for use with [`pcg`].
+#[allow(dead_code)]
+pub fn jacobi_preconditioner(a: &Matrix<f32>) -> Result<Matrix<f32>, MatrixError> {
+    a.as_square()?;
+    check_diagonal(a)?;
+
+    let mut d = Matrix::new(a.width(), a.height());
+    for i in 0..a.width() {
+        d.set(i, i, *a.get(i, i));
+    }
+    Ok(d.recip_scaled(&1.0))
+}
+
+#[allow(dead_code)]
+pub fn jacobi(
+    a: &Matrix<f32>,
+    b: &Matrix<f32>,
+    max_iters: usize,
+    tol: f32,
+) -> Result<Matrix<f32>, MatrixError> {
+    check_system(a, b)?;
+    check_diagonal(a)?;
+
+    let n = a.width();
+    let mut x = Matrix::new(1, n);
+    for _ in 0..max_iters {
+        if (b - a.mul_vec(&x)?)?.norm() <= tol {
+            break;
+        }
+
+        let mut next = Matrix::new(1, n);
+        for i in 0..n {
+            let mut sum = *b.get(i, 0);
+            for j in 0..n {
+                if j != i {
+                    sum -= a.get(i, j) * x.get(j, 0);
+                }
+            }
+            next.set(i, 0, sum / a.get(i, i));
+        }
+        x = next;
+    }
+
+    check_converged((b - a.mul_vec(&x)?)?.norm(), tol, max_iters)?;
+    Ok(x)
+}
+
+#[allow(dead_code)]
+pub fn gauss_seidel(
+    a: &Matrix<f32>,
+    b: &Matrix<f32>,
+    max_iters: usize,
+    tol: f32,
+) -> Result<Matrix<f32>, MatrixError> {
+    check_system(a, b)?;
+    check_diagonal(a)?;
+
+    let n = a.width();
+    let mut x = Matrix::new(1, n);
+    for _ in 0..max_iters {
+        if (b - a.mul_vec(&x)?)?.norm() <= tol {
+            break;
+        }
+
+        // unlike Jacobi, updated entries are used as soon as they are known
+        for i in 0..n {
+            let mut sum = *b.get(i, 0);
+            for j in 0..n {
+                if j != i {
+                    sum -= a.get(i, j) * x.get(j, 0);
+                }
+            }
+            x.set(i, 0, sum / a.get(i, i));
+        }
+    }
+
+    check_converged((b - a.mul_vec(&x)?)?.norm(), tol, max_iters)?;
+    Ok(x)
+}
@@ -1,7 +1,8 @@
 use std::{
     cmp::Ordering,
-    fmt::Display,
-    ops::{Add, Div, Mul, Neg, Rem, Sub},
+    fmt::{Display, LowerHex, UpperHex},
+    ops::{Add, Div, Mul, Neg, Rem, Shl, Shr, Sub},
+    str::FromStr,
 };
 
 #[derive(Clone, Debug)]
@@ -42,31 +43,17 @@ impl LongInt {
     }
 
     fn shift_left(&mut self, by_digits: usize) {
-        let old_len = self.digits.len();
-        self.digits.resize(old_len + by_digits, 0);
-
-        for i in 0..old_len {
-            let i = old_len - i - 1;
-            self.digits[i + by_digits] = self.digits[i];
-        }
+        self.digits.splice(0..0, std::iter::repeat_n(0, by_digits));
     }
 
     fn shift_right(&mut self, by_digits: usize) {
-        let len = self.digits.len();
-
-        let mut copy = 0;
-        for i in by_digits..len {
-            let i = len - i - 1;
-            let t = self.digits[i - by_digits];
-            self.digits[i] = copy;
-            self.digits[i - by_digits] = self.digits[i];
-            copy = t;
-        }
+        let by_digits = by_digits.min(self.digits.len());
+        self.digits.drain(..by_digits);
     }
 
     fn bit_shift_left(&mut self, by_bits: usize) {
         let digit_shift = by_bits >> 3;
-        let bit_shift = by_bits & 3;
+        let bit_shift = by_bits & 7;
         self.shift_left(digit_shift);
 
         let old_len = self.digits.len();
@@ -83,7 +70,7 @@ impl LongInt {
 
     fn bit_shift_right(&mut self, by_bits: usize) {
         let digit_shift = by_bits >> 3;
-        let bit_shift = by_bits & 3;
+        let bit_shift = by_bits & 7;
         self.shift_right(digit_shift);
 
         let old_len = self.digits.len();
@@ -109,6 +96,16 @@ impl LongInt {
         return 0;
     }
 
+    fn signum(&self) -> i8 {
+        if self.actual_length() == 0 {
+            0
+        } else if self.positive {
+            1
+        } else {
+            -1
+        }
+    }
+
     fn get_bit(&self, bit: usize) -> bool {
         let digit = bit / 8;
         let bit = bit - digit * 8;
@@ -136,91 +133,186 @@ impl LongInt {
     }
 
     fn trim(&mut self) {
-        for i in 0..self.digits.len() {
-            let i = self.digits.len() - i - 1;
-            if self.digits[i] != 0 {
-                self.digits.resize(i + 1, 0);
-                return;
+        self.digits.truncate(self.actual_length());
+    }
+
+    /// Parses an optionally signed string of decimal digits.
+    pub fn from_decimal(s: &str) -> Result<Self, ParseLongIntError> {
+        let (positive, digits) = match s.as_bytes().first() {
+            Some(b'-') => (false, &s[1..]),
+            Some(b'+') => (true, &s[1..]),
+            _ => (true, s),
+        };
+        if digits.is_empty() {
+            return Err(ParseLongIntError::NoDigits);
+        }
+        let offset = s.len() - digits.len();
+        if let Some(index) = digits.bytes().position(|b| !b.is_ascii_digit()) {
+            return Err(ParseLongIntError::InvalidDigit {
+                index: index + offset,
+                byte: digits.as_bytes()[index],
+            });
+        }
+
+        // nine digits at a time still fit in a u64 together with the multiplier
+        let mut res: LongInt = 0.into();
+        for chunk in digits.as_bytes().chunks(9) {
+            let value = chunk
+                .iter()
+                .fold(0u64, |acc, d| acc * 10 + (d - b'0') as u64);
+            let scale = LongInt::from(10u64.pow(chunk.len() as u32));
+            res = &(&res * &scale) + &LongInt::from(value);
+        }
+
+        res.trim();
+        res.positive = positive;
+        Ok(res)
+    }
+
+    /// `self += a * b`. When the product has the sign of `self` (or `self` is
+    /// zero) it is accumulated straight into `self.digits`, with no temporaries.
+    pub fn mul_add_assign(&mut self, a: &LongInt, b: &LongInt) {
+        let product_positive = a.positive == b.positive;
+        if self.signum() == 0 {
+            self.positive = product_positive;
+        } else if self.positive != product_positive {
+            *self = &*self + &(a * b);
+            return;
+        }
+
+        let (len_a, len_b) = (a.actual_length(), b.actual_length());
+        if self.digits.len() < len_a + len_b {
+            self.digits.resize(len_a + len_b, 0);
+        }
+
+        for i in 0..len_b {
+            let d = b.digits[i] as u32;
+            let mut carry = 0u32;
+            for j in 0..len_a {
+                let sum = self.digits[i + j] as u32 + a.digits[j] as u32 * d + carry;
+                self.digits[i + j] = sum as u8;
+                carry = sum >> 8;
+            }
+
+            let mut k = i + len_a;
+            while carry > 0 {
+                if k == self.digits.len() {
+                    self.digits.push(0);
+                }
+                let sum = self.digits[k] as u32 + carry;
+                self.digits[k] = sum as u8;
+                carry = sum >> 8;
+                k += 1;
             }
         }
+
+        self.trim();
+    }
+
+    /// Remainder in `[0, |modulus|)`, whatever the signs of `self` and `modulus`.
+    /// Panics if `modulus` is zero, like the division it is built on.
+    pub fn rem_euclid(&self, modulus: &LongInt) -> LongInt {
+        let r = div_ignore_sign(self, modulus).1.abs();
+        if self.signum() < 0 && r.actual_length() > 0 {
+            sub_ignore_sign(&modulus.abs(), &r)
+        } else {
+            r
+        }
+    }
+
+    /// Upper bound on the number of decimal digits of `|self|`, off by at most
+    /// one. Much cheaper than formatting the number.
+    pub fn decimal_digits(&self) -> usize {
+        let Some(top) = self.digits.iter().rposition(|&d| d != 0) else {
+            return 1;
+        };
+        let bits = top * 8 + (8 - self.digits[top].leading_zeros() as usize);
+        (bits as f64 * std::f64::consts::LOG10_2).ceil() as usize
     }
 
     pub fn to_decimal(&self) -> String {
-        if self == &0.into() {
+        self.format_radix(10)
+    }
+
+    /// The value written in base `radix`, with lowercase letters for digits
+    /// past 9 and a leading `-` when negative. Panics if `radix` is not in
+    /// `2..=36`.
+    pub fn format_radix(&self, radix: u32) -> String {
+        let magnitude = self.format_magnitude(radix);
+        if self.signum() < 0 {
+            format!("-{magnitude}")
+        } else {
+            magnitude
+        }
+    }
+
+    fn format_magnitude(&self, radix: u32) -> String {
+        assert!(
+            (2..=36).contains(&radix),
+            "format_radix(): radix {radix} is not in 2..=36"
+        );
+        if self.actual_length() == 0 {
             return "0".to_owned();
         }
 
+        let radix = LongInt::from(radix as i64);
         let mut div = self.abs();
         let mut s = String::new();
 
-        while &div > &0.into() {
+        while div.actual_length() > 0 {
             let digit;
-            (div, digit) = div_ignore_sign(&div, &10.into());
-            s.push(digit.get(0).to_string().chars().next().unwrap());
+            (div, digit) = div_ignore_sign(&div, &radix);
+            s.push(std::char::from_digit(digit.get(0) as u32, 36).unwrap());
         }
 
-        let mut res = if self >= &0.into() {
-            String::new()
-        } else {
-            "-".to_owned()
-        };
-        res += &s.chars().rev().collect::<String>();
-
-        res
+        s.chars().rev().collect()
     }
+}
 
-    fn hex_digit(dec: u8) -> char {
-        match dec {
-            0 => '0',
-            1 => '1',
-            2 => '2',
-            3 => '3',
-            4 => '4',
-            5 => '5',
-            6 => '6',
-            7 => '7',
-            8 => '8',
-            9 => '9',
-            10 => 'A',
-            11 => 'B',
-            12 => 'C',
-            13 => 'D',
-            14 => 'E',
-            15 => 'F',
-            _ => unreachable!(),
-        }
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseLongIntError {
+    NoDigits,
+    InvalidDigit { index: usize, byte: u8 },
+}
 
-    pub fn to_hex(&self) -> String {
-        if self == &0.into() {
-            return "|00|".to_owned();
+impl Display for ParseLongIntError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseLongIntError::NoDigits => write!(f, "no digits to parse"),
+            ParseLongIntError::InvalidDigit { index, byte } => {
+                write!(f, "invalid digit {:?} at byte {}", *byte as char, index)
+            }
         }
+    }
+}
 
-        let mut s = if self >= &0.into() {
-            "|".to_owned()
-        } else {
-            "-|".to_owned()
-        };
-
-        for d in &self.digits {
-            let lo = d % 16;
-            let hi = d / 16;
-            s.push(Self::hex_digit(hi));
-            s.push(Self::hex_digit(lo));
-            s.push('|');
-        }
+impl FromStr for LongInt {
+    type Err = ParseLongIntError;
 
-        s
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_decimal(s)
     }
 }
 
 impl Display for LongInt {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // write!(f, "{}", self.to_hex())
         write!(f, "{}", self.to_decimal())
     }
 }
 
+impl LowerHex for LongInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad_integral(self.signum() >= 0, "0x", &self.format_magnitude(16))
+    }
+}
+
+impl UpperHex for LongInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digits = self.format_magnitude(16).to_uppercase();
+        f.pad_integral(self.signum() >= 0, "0x", &digits)
+    }
+}
+
 fn add_ignore_sign(a: &LongInt, b: &LongInt) -> LongInt {
     let len = usize::max(a.digits.len(), b.digits.len());
     let mut v = Vec::with_capacity(len);
@@ -268,7 +360,7 @@ fn sub_ignore_sign(a: &LongInt, b: &LongInt) -> LongInt {
                     positive: true,
                 },
             );
-            v.push((a.get(i) as u16 + u8::MAX as u16 - b.get(i) as u16) as u8);
+            v.push((a.get(i) as u16 + 256 - b.get(i) as u16) as u8);
         } else {
             v.push(a.get(i) - b.get(i));
         }
@@ -326,8 +418,8 @@ fn div_ignore_sign(n: &LongInt, d: &LongInt) -> (LongInt, LongInt) {
         r.bit_shift_left(1);
         r.set_bit(0, n.get_bit(i));
 
-        if &r >= &d {
-            r = &r - d;
+        if ord_ignore_sign(&r, d) != Some(Ordering::Less) {
+            r = sub_ignore_sign(&r, d);
             q.set_bit(i, true);
         }
     }
@@ -337,6 +429,47 @@ fn div_ignore_sign(n: &LongInt, d: &LongInt) -> (LongInt, LongInt) {
     (q, r)
 }
 
+/// Multiplies the magnitude by `2^rhs`, keeping the sign.
+impl Shl<usize> for &LongInt {
+    type Output = LongInt;
+
+    fn shl(self, rhs: usize) -> Self::Output {
+        let mut res = self.clone();
+        res.bit_shift_left(rhs);
+        res.trim();
+        res
+    }
+}
+
+impl Shl<usize> for LongInt {
+    type Output = LongInt;
+
+    fn shl(self, rhs: usize) -> Self::Output {
+        &self << rhs
+    }
+}
+
+/// Divides the magnitude by `2^rhs`, keeping the sign, so negative values are
+/// rounded towards zero like `/`.
+impl Shr<usize> for &LongInt {
+    type Output = LongInt;
+
+    fn shr(self, rhs: usize) -> Self::Output {
+        let mut res = self.clone();
+        res.bit_shift_right(rhs);
+        res.trim();
+        res
+    }
+}
+
+impl Shr<usize> for LongInt {
+    type Output = LongInt;
+
+    fn shr(self, rhs: usize) -> Self::Output {
+        &self >> rhs
+    }
+}
+
 impl Neg for LongInt {
     type Output = LongInt;
 
@@ -361,7 +494,13 @@ impl Neg for &LongInt {
 
 impl PartialEq for LongInt {
     fn eq(&self, other: &Self) -> bool {
-        self.digits == other.digits && self.positive == other.positive
+        let len = self.actual_length();
+        if len != other.actual_length() {
+            return false;
+        }
+
+        // zero has no sign, and trailing zero digits don't change the value
+        len == 0 || (self.positive == other.positive && self.digits[..len] == other.digits[..len])
     }
 }
 
@@ -384,24 +523,39 @@ fn ord_ignore_sign(a: &LongInt, b: &LongInt) -> Option<Ordering> {
 
 impl PartialOrd for LongInt {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        if self.positive && !other.positive {
-            return Some(Ordering::Greater);
-        }
-
-        if !self.positive && other.positive {
-            return Some(Ordering::Less);
+        match self.signum().cmp(&other.signum()) {
+            Ordering::Equal if self.signum() < 0 => ord_ignore_sign(other, self),
+            Ordering::Equal => ord_ignore_sign(self, other),
+            ord => Some(ord),
         }
-
-        ord_ignore_sign(self, other)
     }
 }
 
 impl From<i32> for LongInt {
     fn from(x: i32) -> Self {
-        Self {
-            digits: x.abs().to_le_bytes().to_vec(),
+        LongInt::from(x as i64)
+    }
+}
+
+impl From<i64> for LongInt {
+    fn from(x: i64) -> Self {
+        let mut res = Self {
+            digits: x.unsigned_abs().to_le_bytes().to_vec(),
             positive: x >= 0,
-        }
+        };
+        res.trim();
+        res
+    }
+}
+
+impl From<u64> for LongInt {
+    fn from(x: u64) -> Self {
+        let mut res = Self {
+            digits: x.to_le_bytes().to_vec(),
+            positive: true,
+        };
+        res.trim();
+        res
     }
 }
 
@@ -690,8 +844,23 @@ impl Rem<LongInt> for &LongInt {
     }
 }
 
+impl From<&LongInt> for f32 {
+    fn from(x: &LongInt) -> Self {
+        // overflows to infinity past f32::MAX, which is what callers want
+        let magnitude = x.digits[..x.actual_length()]
+            .iter()
+            .rev()
+            .fold(0.0f32, |acc, &d| acc * 256.0 + d as f32);
+        if x.positive {
+            magnitude
+        } else {
+            -magnitude
+        }
+    }
+}
+
 impl Into<f32> for LongInt {
     fn into(self) -> f32 {
-        todo!()
+        f32::from(&self)
     }
 }
@@ -1,53 +1,407 @@
-use std::{fs::File, path::PathBuf, time::Instant};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 use crate::{
+    eigen::is_tridiagonal,
+    fraction::Fraction,
     io::{read_mat, write_mat_complex, write_mat_f32, Either},
+    longint::LongInt,
     measure,
     number::{NumNonRef, NumRef},
 };
 
 use super::matrix::*;
 
-pub fn lu_decomposition<T>(mat: &Matrix<T>) -> Result<(Matrix<T>, Matrix<T>), MatrixError>
+/// `(perm, L, U)` with `PA = LU`, where row `i` of `PA` is row `perm[i]` of `A`.
+pub type PivotedLu<T> = (Vec<usize>, Matrix<T>, Matrix<T>);
+
+/// Pivots no larger than this times the largest `|a_ij|` make
+/// [`lu_decomposition`] report the matrix as singular. Exact types only ever
+/// reject a pivot that is exactly zero.
+pub const SINGULAR_EPSILON: f32 = 1e-12;
+
+/// Row updates per layer below which [`par_lu_decomposition`] stays serial,
+/// spawning threads costs more than the arithmetic.
+const PARALLEL_LU_MIN_WORK: usize = 1 << 16;
+
+/// LU decomposition with partial pivoting: at every layer the remaining row
+/// with the largest pivot candidate (by `norm()`) is swapped into place.
+pub fn lu_decomposition<T>(mat: &Matrix<T>) -> Result<PivotedLu<T>, MatrixError>
 where
     T: NumNonRef,
     for<'a> &'a T: NumRef<T>,
 {
-    if mat.width() != mat.height() {
-        return Err(MatrixError::NotSquare);
+    lu_decomposition_with_options(mat, 1.0, SINGULAR_EPSILON)
+}
+
+/// [`lu_decomposition`] that only swaps rows when the largest candidate beats
+/// the pivot already in place by more than `pivot_threshold` times. `1.0` is
+/// plain partial pivoting, larger values keep the original row order unless a
+/// pivot is much worse, and a zero pivot is always swapped out.
+///
+/// A pivot with `norm() <= singular_epsilon · max|a_ij|` gives `NotRegular`,
+/// pass `0.0` to only reject exact zeros. The threshold is ignored for exact
+/// types, see [`NumNonRef::is_exact`].
+pub fn lu_decomposition_with_options<T>(
+    mat: &Matrix<T>,
+    pivot_threshold: f32,
+    singular_epsilon: f32,
+) -> Result<PivotedLu<T>, MatrixError>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    decompose(mat, pivot_threshold, singular_epsilon, eliminate_rows)
+}
+
+/// [`lu_decomposition`] that spreads the row updates below each pivot over
+/// scoped threads while the trailing block is large enough to pay for it.
+/// Every row is updated exactly as in the serial version, so the factors are
+/// the same.
+#[allow(dead_code)]
+pub fn par_lu_decomposition(mat: &Matrix<f32>) -> Result<PivotedLu<f32>, MatrixError> {
+    decompose(mat, 1.0, SINGULAR_EPSILON, par_eliminate_rows)
+}
+
+/// Subtracts multiples of `pivot_row` from every row in `bottom`, leaving the
+/// multipliers in column `layer`.
+fn eliminate_rows<T>(bottom: &mut [T], pivot_row: &[T], layer: usize)
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    let width = pivot_row.len();
+    let a = &pivot_row[layer];
+    for row in bottom.chunks_mut(width) {
+        for j in layer + 1..width {
+            row[j] = &row[j] - &(&pivot_row[j] * &row[layer]) / a;
+        }
+        row[layer] = &row[layer] / a;
     }
+}
+
+fn par_eliminate_rows(bottom: &mut [f32], pivot_row: &[f32], layer: usize) {
+    let width = pivot_row.len();
+    let rows = bottom.len() / width;
+    if rows * (width - layer) < PARALLEL_LU_MIN_WORK {
+        return eliminate_rows(bottom, pivot_row, layer);
+    }
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let rows_per_thread = rows.div_ceil(threads);
+    std::thread::scope(|scope| {
+        for block in bottom.chunks_mut(rows_per_thread * width) {
+            scope.spawn(move || eliminate_rows(block, pivot_row, layer));
+        }
+    });
+}
+
+fn decompose<T, F>(
+    mat: &Matrix<T>,
+    pivot_threshold: f32,
+    singular_epsilon: f32,
+    eliminate: F,
+) -> Result<PivotedLu<T>, MatrixError>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+    F: Fn(&mut [T], &[T], usize),
+{
+    mat.as_square()?;
     let width = mat.width();
+    let zero = T::zero();
 
-    let mut l = Vec::with_capacity(width * width);
-    l.resize(width * width, 0.0.into());
-    let mut u = l.clone();
+    // L and U are built in place: the strict lower triangle of `d` collects the
+    // multipliers and the upper triangle is U. Layer k touches the trailing
+    // (n - k - 1)^2 block once, so the whole thing is O(n^3) arithmetic with
+    // no clones inside the loop.
     let mut d = mat.elems_raw().to_owned();
+    let mut perm: Vec<usize> = (0..width).collect();
 
-    for layer in 0..mat.width() {
-        let a = d[layer * width + layer].clone();
-        if a.clone() == 0.0.into() {
-            return Err(MatrixError::NotRegular);
+    // relative to the scale of the matrix, so that scaling it does not change
+    // whether it is singular
+    let tol = if T::is_exact() {
+        0.0
+    } else {
+        singular_epsilon * d.iter().map(|x| x.norm()).fold(0.0, f32::max)
+    };
+
+    for layer in 0..width {
+        let mut best = layer;
+        for i in layer + 1..width {
+            if d[i * width + layer].norm() > d[best * width + layer].norm() {
+                best = i;
+            }
+        }
+        if best != layer
+            && d[best * width + layer].norm() > pivot_threshold * d[layer * width + layer].norm()
+        {
+            // whole rows move, multipliers included, so that PA = LU holds
+            for j in 0..width {
+                d.swap(layer * width + j, best * width + j);
+            }
+            perm.swap(layer, best);
         }
 
-        l[layer * width + layer] = 1.0.into();
-        u[layer * width + layer] = a.clone();
+        let (top, bottom) = d.split_at_mut((layer + 1) * width);
+        let pivot_row = &top[layer * width..];
+        let a = &pivot_row[layer];
+        if a == &zero || (tol > 0.0 && a.norm() <= tol) {
+            return Err(MatrixError::NotRegular);
+        }
 
-        //this can be rewritten to run in parallel
-        for i in layer + 1..width {
-            l[i * width + layer] = &d[i * width + layer] / &a;
-            u[layer * width + i] = d[layer * width + i].clone();
+        // rows below the pivot are independent of each other
+        eliminate(bottom, pivot_row, layer);
+    }
 
-            for j in layer + 1..width {
-                d[i * width + j] =
-                    &d[i * width + j] - &(&d[layer * width + j] * &d[i * width + layer]) / &a;
-            }
+    let mut l = Vec::with_capacity(width * width);
+    let mut u = Vec::with_capacity(width * width);
+    for (k, x) in d.into_iter().enumerate() {
+        let (i, j) = (k / width, k % width);
+        if i > j {
+            l.push(x);
+            u.push(zero.clone());
+        } else {
+            l.push(if i == j { T::one() } else { zero.clone() });
+            u.push(x);
         }
     }
 
     let l = Matrix::from_vec(l, width)?;
     let u = Matrix::from_vec(u, width)?;
 
-    Ok((l, u))
+    Ok((perm, l, u))
+}
+
+fn permute_rows<T>(mat: &Matrix<T>, perm: &[usize]) -> Matrix<T>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    let mut res = Matrix::new(mat.width(), mat.height());
+    for (i, &p) in perm.iter().enumerate() {
+        for j in 0..mat.width() {
+            res.set(i, j, mat.get(p, j).clone());
+        }
+    }
+    res
+}
+
+fn is_permutation(perm: &[usize]) -> bool {
+    let mut seen = vec![false; perm.len()];
+    for &p in perm {
+        if p >= perm.len() || seen[p] {
+            return false;
+        }
+        seen[p] = true;
+    }
+    true
+}
+
+/// The matrix `P` with `P[i][perm[i]] = 1`, so that `PA` is `A` permuted by `perm`.
+pub fn permutation_matrix(perm: &[usize]) -> Matrix<f32> {
+    let mut p = Matrix::new(perm.len(), perm.len());
+    for (i, &j) in perm.iter().enumerate() {
+        p.set(i, j, 1.0);
+    }
+    p
+}
+
+/// Inverse of `permutation_matrix`, rejecting anything that is not a permutation.
+pub fn permutation_from_matrix(p: &Matrix<f32>) -> Result<Vec<usize>, MatrixError> {
+    p.as_square()?;
+
+    let mut perm = Vec::with_capacity(p.height());
+    for row in p.rows() {
+        let ones: Vec<_> = (0..p.width()).filter(|&j| *row.get(0, j) == 1.0).collect();
+        let zeros = (0..p.width()).filter(|&j| *row.get(0, j) == 0.0).count();
+        if ones.len() != 1 || zeros != p.width() - 1 {
+            return Err(MatrixError::InvalidFileFormat);
+        }
+        perm.push(ones[0]);
+    }
+
+    if !is_permutation(&perm) {
+        return Err(MatrixError::InvalidFileFormat);
+    }
+    Ok(perm)
+}
+
+/// `∥LU - PA∥` for a pivoted factorization of `mat`.
+pub fn lu_reconstruction_error<T>(
+    mat: &Matrix<T>,
+    perm: &[usize],
+    l: &Matrix<T>,
+    u: &Matrix<T>,
+) -> Result<f32, MatrixError>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    Ok(((l * u)? - permute_rows(mat, perm))?.norm())
+}
+
+#[allow(dead_code)]
+impl Matrix<Fraction<LongInt>> {
+    /// Exact determinant by Gaussian elimination over the rationals. Any
+    /// nonzero pivot is as good as another here, so rows are only swapped to
+    /// step over zeros.
+    pub fn determinant(&self) -> Result<Fraction<LongInt>, MatrixError> {
+        self.as_square()?;
+
+        let n = self.width();
+        let zero: Fraction<LongInt> = 0.0.into();
+        let mut d = self.clone();
+        let mut det: Fraction<LongInt> = 1.0.into();
+
+        for k in 0..n {
+            let Some(p) = (k..n).find(|&i| d.get(i, k) != &zero) else {
+                return Ok(zero);
+            };
+            if p != k {
+                for j in k..n {
+                    let t = d.get(k, j).clone();
+                    d.set(k, j, d.get(p, j).clone());
+                    d.set(p, j, t);
+                }
+                det = -det;
+            }
+
+            let pivot = d.get(k, k).clone();
+            for i in k + 1..n {
+                let m = d.get(i, k) / &pivot;
+                for j in k + 1..n {
+                    let v = d.get(i, j) - &(&m * d.get(k, j));
+                    d.set(i, j, v);
+                }
+            }
+            det = det * pivot;
+        }
+
+        Ok(det)
+    }
+}
+
+/// An LU factorization of a square matrix that can be reused to solve
+/// `Ax = b` for many right-hand sides without factoring `A` again.
+pub struct LuFactorization<T>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    perm: Vec<usize>,
+    l: Matrix<T>,
+    u: Matrix<T>,
+}
+
+impl<T> LuFactorization<T>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    pub fn new(mat: &Matrix<T>) -> Result<Self, MatrixError> {
+        let (perm, l, u) = lu_decomposition(mat)?;
+        Ok(Self { perm, l, u })
+    }
+
+    /// Factors computed without pivoting, `A = LU`.
+    pub fn from_factors(l: Matrix<T>, u: Matrix<T>) -> Result<Self, MatrixError> {
+        let perm = (0..l.height()).collect();
+        Self::from_pivoted_factors(perm, l, u)
+    }
+
+    pub fn from_pivoted_factors(
+        perm: Vec<usize>,
+        l: Matrix<T>,
+        u: Matrix<T>,
+    ) -> Result<Self, MatrixError> {
+        if l.width() != l.height()
+            || u.width() != u.height()
+            || l.width() != u.width()
+            || perm.len() != l.height()
+        {
+            return Err(MatrixError::SizeMismatch);
+        }
+        if !is_permutation(&perm) {
+            return Err(MatrixError::InvalidFileFormat);
+        }
+
+        Ok(Self { perm, l, u })
+    }
+
+    pub fn permutation(&self) -> &[usize] {
+        &self.perm
+    }
+
+    pub fn l(&self) -> &Matrix<T> {
+        &self.l
+    }
+
+    pub fn u(&self) -> &Matrix<T> {
+        &self.u
+    }
+
+    pub fn solve(&self, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        if b.height() != self.perm.len() {
+            return Err(MatrixError::SizeMismatch);
+        }
+        gauss_from_lu(&self.l, &self.u, &permute_rows(b, &self.perm))
+    }
+}
+
+/// Solves `Ax = b` by LU, then runs `steps` passes of iterative refinement:
+/// the residual `r = b - Ax` is accumulated in f64 and the correction from
+/// `A·dx = r` is added to `x`, reusing the same factorization.
+#[allow(dead_code)]
+pub fn solve_with_refinement(
+    a: &Matrix<f32>,
+    b: &Matrix<f32>,
+    steps: usize,
+) -> Result<Matrix<f32>, MatrixError> {
+    let lu = LuFactorization::new(a)?;
+    let mut x = lu.solve(b)?;
+
+    for _ in 0..steps {
+        let r = residual_f64(a, &x, b);
+        if r.norm() == 0.0 {
+            break;
+        }
+        let dx = lu.solve(&r)?;
+        x = (x + dx)?;
+    }
+
+    Ok(x)
+}
+
+/// Solves `(A - μI)x = b` without touching `a`. Fails with `NotRegular` when
+/// `μ` is an eigenvalue of `A`.
+#[allow(dead_code)]
+pub fn shifted_solve(
+    a: &Matrix<f32>,
+    mu: f32,
+    b: &Matrix<f32>,
+) -> Result<Matrix<f32>, MatrixError> {
+    let mut shifted = a.as_square()?.clone();
+    shifted.shift_diagonal(&mu);
+    LuFactorization::new(&shifted)?.solve(b)
+}
+
+fn residual_f64(a: &Matrix<f32>, x: &Matrix<f32>, b: &Matrix<f32>) -> Matrix<f32> {
+    let n = a.height();
+    let mut r = Matrix::zeros(x.width(), n);
+    for i in 0..n {
+        for c in 0..x.width() {
+            let mut sum = *b.get(i, c) as f64;
+            for k in 0..a.width() {
+                sum -= *a.get(i, k) as f64 * *x.get(k, c) as f64;
+            }
+            r.set(i, c, sum as f32);
+        }
+    }
+    r
 }
 
 pub fn gauss_from_lu<T>(
@@ -61,141 +415,297 @@ where
 {
     if l.width() != l.height()
         || u.width() != u.height()
-        || b.width() != 1
         || b.height() != l.height()
         || l.width() != u.width()
     {
         return Err(MatrixError::SizeMismatch);
     }
 
-    let v = l_gauss(l, b);
-    let x = u_gauss(u, &v);
-
-    Ok(x)
+    let v = l.solve_lower_triangular(b)?;
+    u.solve_upper_triangular(&v)
 }
 
-fn l_gauss<T>(l: &Matrix<T>, b: &Matrix<T>) -> Matrix<T>
+/// Solves `AX = B` in one pass: forward elimination with partial pivoting on
+/// the augmented `[A | B]`, then back-substitution. L and U are never stored.
+#[allow(dead_code)]
+pub fn gauss_eliminate<T>(a: &Matrix<T>, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError>
 where
     T: NumNonRef,
     for<'a> &'a T: NumRef<T>,
 {
-    let mut x = Matrix::new(1, l.width());
-    for i in 0..l.width() {
-        let mut xi = b.get(i, 0).clone();
-        for j in 0..i {
-            xi = xi - l.get(i, j) * x.get(j, 0);
+    a.as_square()?;
+    let n = a.height();
+    let aug = a.hstack(b)?;
+    let width = aug.width();
+    let rhs = b.width();
+    let zero = T::zero();
+    let mut d = aug.elems_raw().to_owned();
+
+    for layer in 0..n {
+        let mut best = layer;
+        for i in layer + 1..n {
+            if d[i * width + layer].norm() > d[best * width + layer].norm() {
+                best = i;
+            }
+        }
+        if best != layer {
+            for j in layer..width {
+                d.swap(layer * width + j, best * width + j);
+            }
+        }
+
+        let (top, bottom) = d.split_at_mut((layer + 1) * width);
+        let pivot_row = &top[layer * width..];
+        let pivot = &pivot_row[layer];
+        if pivot == &zero {
+            return Err(MatrixError::NotRegular);
+        }
+
+        for row in bottom.chunks_mut(width) {
+            let factor = &row[layer] / pivot;
+            for j in layer + 1..width {
+                row[j] = &row[j] - &(&pivot_row[j] * &factor);
+            }
+        }
+    }
+
+    let mut x = vec![zero; n * rhs];
+    for i in (0..n).rev() {
+        for c in 0..rhs {
+            let mut sum = d[i * width + n + c].clone();
+            for k in i + 1..n {
+                sum = &sum - &(&d[i * width + k] * &x[k * rhs + c]);
+            }
+            x[i * rhs + c] = &sum / &d[i * width + i];
+        }
+    }
+
+    Matrix::from_vec(x, rhs)
+}
+
+/// Solves a tridiagonal system in O(n) with the Thomas algorithm. Entries
+/// outside the three central diagonals must be exactly zero.
+#[allow(dead_code)]
+pub fn solve_tridiagonal(mat: &Matrix<f32>, b: &Matrix<f32>) -> Result<Matrix<f32>, MatrixError> {
+    let n = mat.width();
+    if n != mat.height() {
+        return Err(MatrixError::NotSquare);
+    }
+    if b.width() != 1 || b.height() != n {
+        return Err(MatrixError::SizeMismatch);
+    }
+    if !is_tridiagonal(mat, 0.0) {
+        return Err(MatrixError::NotTridiagonal);
+    }
+
+    let mut c = Vec::with_capacity(n);
+    let mut d = Vec::with_capacity(n);
+    for i in 0..n {
+        let (sub, prev_c, prev_d) = if i > 0 {
+            (*mat.get(i, i - 1), c[i - 1], d[i - 1])
+        } else {
+            (0.0, 0.0, 0.0)
+        };
+
+        let denom = mat.get(i, i) - sub * prev_c;
+        if denom == 0.0 {
+            return Err(MatrixError::NotRegular);
         }
-        x.set(i, 0, xi);
+        c.push(if i + 1 < n {
+            mat.get(i, i + 1) / denom
+        } else {
+            0.0
+        });
+        d.push((b.get(i, 0) - sub * prev_d) / denom);
     }
 
-    x
+    let mut x = Matrix::new(1, n);
+    for i in (0..n).rev() {
+        let next = if i + 1 < n { *x.get(i + 1, 0) } else { 0.0 };
+        x.set(i, 0, d[i] - c[i] * next);
+    }
+
+    Ok(x)
 }
 
-fn u_gauss<T>(u: &Matrix<T>, b: &Matrix<T>) -> Matrix<T>
+/// The factors from [`lu_decomposition`] together with how long it took and
+/// `∥LU - PA∥`.
+pub struct LuReport<T>
 where
     T: NumNonRef,
     for<'a> &'a T: NumRef<T>,
 {
-    let mut x = Matrix::new(1, u.width());
-    for i in 0..u.width() {
-        let mut xi = b.get(u.width() - i - 1, 0).clone();
-        for j in 0..i {
-            xi = xi - u.get(u.width() - i - 1, u.width() - j - 1) * x.get(u.width() - j - 1, 0);
-        }
-        x.set(
-            u.width() - i - 1,
-            0,
-            &xi / u.get(u.width() - i - 1, u.width() - i - 1),
-        );
-    }
-    x
+    pub perm: Vec<usize>,
+    pub l: Matrix<T>,
+    pub u: Matrix<T>,
+    pub duration: Duration,
+    pub reconstruction_error: f32,
+}
+
+pub fn lu_report<T>(mat: &Matrix<T>) -> Result<LuReport<T>, MatrixError>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    let ((perm, l, u), duration) = measure!(lu_decomposition(mat)?);
+    let reconstruction_error = lu_reconstruction_error(mat, &perm, &l, &u)?;
+
+    Ok(LuReport {
+        perm,
+        l,
+        u,
+        duration,
+        reconstruction_error,
+    })
 }
 
 pub fn make_lu(dir: &PathBuf, problem: usize) -> Result<(), MatrixError> {
     let file_path = dir.join(format!("Amat{problem}.m"));
     let l_path = dir.join(format!("Lmat{problem}.m"));
     let u_path = dir.join(format!("Umat{problem}.m"));
+    let p_path = dir.join(format!("Pmat{problem}.m"));
 
     println!("Problem {}", problem);
     let (mat, _) = read_mat(&mut File::open(&file_path)?)?;
 
-    match mat {
+    let perm = match mat {
         Either::Left(mat) => {
-            let ((l, u), lu_duration) = measure!(lu_decomposition(&mat)?);
-            write_mat_f32(&l, &l_path)?;
-            write_mat_f32(&u, &u_path)?;
+            let report = lu_report(&mat)?;
+            write_mat_f32(&report.l, &l_path)?;
+            write_mat_f32(&report.u, &u_path)?;
 
             println!(
-                "\tTook {}μs, ∥LU - A∥ = {}",
-                lu_duration.as_micros(),
-                ((l * u)? - &mat)?.norm()
+                "\tTook {}μs, ∥LU - PA∥ = {}",
+                report.duration.as_micros(),
+                report.reconstruction_error
             );
+            report.perm
         }
         Either::Right(mat) => {
-            let ((l, u), lu_duration) = measure!(lu_decomposition(&mat)?);
-            write_mat_complex(&l, &l_path)?;
-            write_mat_complex(&u, &u_path)?;
+            let report = lu_report(&mat)?;
+            write_mat_complex(&report.l, &l_path)?;
+            write_mat_complex(&report.u, &u_path)?;
 
             println!(
-                "\tTook {}μs, ∥LU - A∥ = {}",
-                lu_duration.as_micros(),
-                ((l * u)? - &mat)?.norm()
+                "\tTook {}μs, ∥LU - PA∥ = {}",
+                report.duration.as_micros(),
+                report.reconstruction_error
             );
+            report.perm
+        }
+    };
+
+    // without row swaps the output is the same as before pivoting existed, and
+    // a Pmat left over from an earlier run must not apply to the new factors
+    if perm.iter().enumerate().all(|(i, &p)| i == p) {
+        match std::fs::remove_file(&p_path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
         }
+    } else {
+        write_mat_f32(&permutation_matrix(&perm), &p_path)?;
     }
 
     Ok(())
 }
 
-pub fn lu_gauss(dir: &PathBuf, problem: usize) -> Result<(), MatrixError> {
-    let (b, _) = read_mat(&mut File::open(dir.join(format!("bvec{problem}.m")))?)?;
-    let (l, u) = match (
-        File::open(dir.join(format!("Lmat{problem}.m"))),
-        File::open(dir.join(format!("Umat{problem}.m"))),
-    ) {
-        (Ok(mut l), Ok(mut u)) => (read_mat(&mut l)?.0, read_mat(&mut u)?.0),
-        _ => {
-            let (a, _) = read_mat(&mut File::open(dir.join(format!("Amat{problem}.m")))?)?;
-            match a {
-                Either::Left(a) => {
-                    let (l, u) = lu_decomposition(&a)?;
-                    (Either::Left(l), Either::Left(u))
-                }
-                Either::Right(a) => {
-                    let (l, u) = lu_decomposition(&a)?;
-                    (Either::Right(l), Either::Right(u))
+/// Where [`solve_lu`] or [`solve_qr`](crate::qr::solve_qr) wrote `x`, the
+/// residual of the factored system and how long the solve took.
+#[allow(dead_code)]
+pub struct SolveReport {
+    pub x_path: PathBuf,
+    pub residual: f32,
+    pub duration: Duration,
+}
+
+/// Solves `Ax = b` for `bvec{problem}.m`, reusing `Lmat`/`Umat`/`Pmat` if they
+/// exist and factoring `Amat` otherwise, and writes `xvec{problem}.m`. The
+/// residual is `∥LUx - Pb∥`.
+pub fn solve_lu(dir: &Path, problem: usize) -> Result<SolveReport, MatrixError> {
+    let b_name = format!("bvec{problem}.m");
+    let l_name = format!("Lmat{problem}.m");
+    let u_name = format!("Umat{problem}.m");
+    let a_name = format!("Amat{problem}.m");
+
+    let (b, _) = read_mat(&mut File::open(dir.join(&b_name))?)?;
+    let (lu, lu_name) = match (File::open(dir.join(&l_name)), File::open(dir.join(&u_name))) {
+        (Ok(mut l), Ok(mut u)) => {
+            // factors written before pivoting existed come without a Pmat
+            let perm = match File::open(dir.join(format!("Pmat{problem}.m"))) {
+                Ok(mut p) => match read_mat(&mut p)?.0 {
+                    Either::Left(p) => Some(permutation_from_matrix(&p)?),
+                    Either::Right(_) => return Err(MatrixError::InvalidFileFormat),
+                },
+                Err(_) => None,
+            };
+
+            let lu = match (read_mat(&mut l)?.0, read_mat(&mut u)?.0) {
+                (Either::Left(l), Either::Left(u)) => Either::Left(match perm {
+                    Some(perm) => LuFactorization::from_pivoted_factors(perm, l, u)?,
+                    None => LuFactorization::from_factors(l, u)?,
+                }),
+                (Either::Right(l), Either::Right(u)) => Either::Right(match perm {
+                    Some(perm) => LuFactorization::from_pivoted_factors(perm, l, u)?,
+                    None => LuFactorization::from_factors(l, u)?,
+                }),
+                (l, _) => {
+                    return Err(MatrixError::real_complex_mismatch(
+                        l.is_left(),
+                        l_name,
+                        u_name,
+                    ))
                 }
-            }
+            };
+            (lu, l_name)
+        }
+        _ => {
+            let (a, _) = read_mat(&mut File::open(dir.join(&a_name))?)?;
+            let lu = match a {
+                Either::Left(a) => Either::Left(LuFactorization::new(&a)?),
+                Either::Right(a) => Either::Right(LuFactorization::new(&a)?),
+            };
+            (lu, a_name)
         }
     };
 
-    println!("Problem {}", problem);
-
-    match b {
-        Either::Left(b) => {
-            let l = l.unwrap_left();
-            let u = u.unwrap_left();
-            let (x, duration) = measure!(gauss_from_lu(l, u, &b)?);
-            write_mat_f32(&x, &dir.join(format!("xvec{problem}.m")))?;
-
-            println!(
-                "\tTook {}μs, ∥LUx - b∥ = {}",
-                duration.as_micros(),
-                ((l * (u * x)?)? - b)?.norm()
-            );
+    let x_path = dir.join(format!("xvec{problem}.m"));
+    let (residual, duration) = match (lu, b) {
+        (Either::Left(lu), Either::Left(b)) => {
+            let (x, duration) = measure!(lu.solve(&b)?);
+            write_mat_f32(&x, &x_path)?;
+            let lux = lu.l().mul_vec(&lu.u().mul_vec(&x)?)?;
+            ((lux - permute_rows(&b, lu.permutation()))?.norm(), duration)
         }
-        Either::Right(b) => {
-            let l = l.unwrap_right();
-            let u = u.unwrap_right();
-            let (x, duration) = measure!(gauss_from_lu(l, u, &b)?);
-            write_mat_complex(&x, &dir.join(format!("xvec{problem}.m")))?;
-            println!(
-                "\tTook {}μs, ∥LUx - b∥ = {}",
-                duration.as_micros(),
-                ((l * (u * x)?)? - b)?.norm()
-            );
+        (Either::Right(lu), Either::Right(b)) => {
+            let (x, duration) = measure!(lu.solve(&b)?);
+            write_mat_complex(&x, &x_path)?;
+            let lux = lu.l().mul_vec(&lu.u().mul_vec(&x)?)?;
+            ((lux - permute_rows(&b, lu.permutation()))?.norm(), duration)
         }
-    }
+        (lu, _) => {
+            return Err(MatrixError::real_complex_mismatch(
+                lu.is_left(),
+                lu_name,
+                b_name,
+            ))
+        }
+    };
+
+    Ok(SolveReport {
+        x_path,
+        residual,
+        duration,
+    })
+}
+
+pub fn lu_gauss(dir: &PathBuf, problem: usize) -> Result<(), MatrixError> {
+    let report = solve_lu(dir, problem)?;
+    println!("Problem {}", problem);
+    println!(
+        "\tTook {}μs, ∥LUx - Pb∥ = {}",
+        report.duration.as_micros(),
+        report.residual
+    );
 
     Ok(())
 }
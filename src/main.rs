@@ -2,18 +2,24 @@ use std::path::PathBuf;
 
 use eigen::find_poly;
 use lu::{lu_gauss, make_lu};
-use qr::{make_qr, qr_gauss};
+use qr::{bench_qr, make_qr, qr_gauss};
 
+mod banded;
 mod complex;
 mod eigen;
 mod fraction;
 mod io;
+mod iterative;
 mod longint;
 mod lu;
 mod matrix;
 mod number;
 mod poly;
 mod qr;
+mod sparse;
+mod svd;
+#[cfg(test)]
+mod tests;
 
 #[macro_export]
 macro_rules! measure {
@@ -83,60 +89,118 @@ fn test_all() {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
 enum Operation {
     MakeLu,
     LuGauss,
     MakeQr,
     QrGauss,
     FindPoly,
+    BenchQr,
+}
+
+/// Every operation under the name it is given on the command line, the
+/// parser and the usage text are both built from this.
+const OPERATIONS: [(&str, Operation); 6] = [
+    ("make_lu", Operation::MakeLu),
+    ("lu_gauss", Operation::LuGauss),
+    ("make_qr", Operation::MakeQr),
+    ("qr_gauss", Operation::QrGauss),
+    ("find_poly", Operation::FindPoly),
+    ("bench_qr", Operation::BenchQr),
+];
+
+fn operation_names(separator: &str) -> String {
+    let names: Vec<_> = OPERATIONS.iter().map(|(name, _)| *name).collect();
+    names.join(separator)
 }
 
 impl TryFrom<String> for Operation {
     type Error = String;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        if value == "make_lu" {
-            Ok(Operation::MakeLu)
-        } else if value == "lu_gauss" {
-            Ok(Operation::LuGauss)
-        } else if value == "make_qr" {
-            Ok(Operation::MakeQr)
-        } else if value == "qr_gauss" {
-            Ok(Operation::QrGauss)
-        } else if value == "find_poly" {
-            Ok(Operation::FindPoly)
-        } else {
-            Err(format!("{value}: unknown operation"))
-        }
+        OPERATIONS
+            .iter()
+            .find(|(name, _)| *name == value)
+            .map(|&(_, operation)| operation)
+            .ok_or_else(|| format!("{value}: unknown operation"))
     }
 }
 
-fn get_args() -> Option<(Operation, PathBuf, usize)> {
-    let args: Vec<_> = std::env::args().collect();
+#[derive(Debug)]
+enum ArgsError {
+    MissingArguments,
+    UnknownOperation(String),
+    BadDirectory(String),
+    BadTask(String),
+}
+
+impl std::fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::MissingArguments => write!(f, "expected 3 arguments"),
+            ArgsError::UnknownOperation(op) => write!(
+                f,
+                "unknown operation '{op}', expected one of: {}",
+                operation_names(", ")
+            ),
+            ArgsError::BadDirectory(dir) => write!(f, "'{dir}' is not an existing directory"),
+            ArgsError::BadTask(task) => write!(f, "matrix number '{task}' is not an integer"),
+        }
+    }
+}
 
-    let operation = args.get(1)?.to_owned();
-    let dir = args.get(2)?.to_owned();
-    let dir = std::fs::canonicalize(dir).ok()?;
-    let task = usize::from_str_radix(args.get(3)?, 10).ok()?;
+/// Parses `{operation} {matrix directory} {matrix number}`, `args[0]` being
+/// the program name.
+fn parse_args(args: &[String]) -> Result<(Operation, PathBuf, usize), ArgsError> {
+    let (operation, dir, task) = match args {
+        [_, operation, dir, task, ..] => (operation, dir, task),
+        _ => return Err(ArgsError::MissingArguments),
+    };
 
-    Some((Operation::try_from(operation).ok()?, dir, task))
+    let operation = Operation::try_from(operation.to_owned())
+        .map_err(|_| ArgsError::UnknownOperation(operation.to_owned()))?;
+    let dir = std::fs::canonicalize(dir)
+        .ok()
+        .filter(|d| d.is_dir())
+        .ok_or_else(|| ArgsError::BadDirectory(dir.to_owned()))?;
+    let task = task
+        .parse()
+        .map_err(|_| ArgsError::BadTask(task.to_owned()))?;
+
+    Ok((operation, dir, task))
 }
 
 fn main() {
     // lu_gauss(&std::fs::canonicalize("matrices").unwrap(), 4).unwrap();
 
-    let(operation,dir,task) = get_args().expect("Usage: cargo run --release {make_lu|lu_gauss|make_qr|qr_gauss|find_poly} {matrix directory} {matrix number}");
+    let args: Vec<_> = std::env::args().collect();
+    let (operation, dir, task) = match parse_args(&args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            eprintln!(
+                "Usage: cargo run --release {{{}}} {{matrix directory}} {{matrix number}}",
+                operation_names("|")
+            );
+            std::process::exit(1);
+        }
+    };
 
     let res = match operation {
         Operation::MakeLu => make_lu(&dir, task),
         Operation::LuGauss => lu_gauss(&dir, task),
         Operation::MakeQr => make_qr(&dir, task),
         Operation::QrGauss => qr_gauss(&dir, task),
-        Operation::FindPoly => find_poly(&dir, task),
+        Operation::FindPoly => find_poly(&dir, task).map(|_| ()),
+        Operation::BenchQr => bench_qr(&dir, task),
     };
 
     match res {
         Ok(_) => println!("Done!"),
-        Err(e) => println!("Error: {e}"),
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
     }
 }
@@ -1,6 +1,6 @@
 use std::{
     fmt::Display,
-    ops::{Add, Div, Mul, Sub},
+    ops::{Add, Div, Mul, Neg, Sub},
 };
 
 use crate::number::{NumNonRef, NumRef};
@@ -14,7 +14,29 @@ pub enum MatrixError {
     SizeMismatch,
     UnexpectedAnswer,
     NotTridiagonal,
+    NotBanded,
     UnsopportedOperation,
+    DidNotConverge {
+        iterations: usize,
+        residual: f32,
+    },
+    TooManyDigits {
+        max_digits: usize,
+    },
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// [`Matrix::toeplitz`] was given a first column and a first row that
+    /// disagree on the shared corner `a_00`.
+    ToeplitzCornerMismatch,
+    /// Two inputs that have to be combined were read as a real and a complex
+    /// matrix, named by their files.
+    RealComplexMismatch {
+        real: String,
+        complex: String,
+    },
 }
 
 impl Display for MatrixError {
@@ -27,17 +49,64 @@ impl Display for MatrixError {
             MatrixError::SizeMismatch => write!(f, "SizeMismatch"),
             MatrixError::UnexpectedAnswer => write!(f, "UnexpectedAnswer"),
             MatrixError::NotTridiagonal => write!(f, "NotTridiagnoal"),
+            MatrixError::NotBanded => write!(f, "NotBanded"),
             MatrixError::UnsopportedOperation => write!(f, "Unsopported Operation"),
+            MatrixError::DidNotConverge {
+                iterations,
+                residual,
+            } => write!(
+                f,
+                "DidNotConverge after {} iterations, residual {}",
+                iterations, residual
+            ),
+            MatrixError::TooManyDigits { max_digits } => {
+                write!(f, "TooManyDigits: a number exceeded {} digits", max_digits)
+            }
+            MatrixError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "RaggedRow: row {} has {} entries, expected {}",
+                row, found, expected
+            ),
+            MatrixError::ToeplitzCornerMismatch => write!(
+                f,
+                "ToeplitzCornerMismatch: first_col[0] and first_row[0] differ"
+            ),
+            MatrixError::RealComplexMismatch { real, complex } => write!(
+                f,
+                "RealComplexMismatch: {} is real but {} is complex",
+                real, complex
+            ),
         }
     }
 }
 
+impl MatrixError {
+    /// The error for combining the matrices read from `first` and `second`
+    /// when exactly one of them is complex.
+    pub fn real_complex_mismatch(first_is_real: bool, first: String, second: String) -> Self {
+        let (real, complex) = if first_is_real {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        MatrixError::RealComplexMismatch { real, complex }
+    }
+}
+
 impl From<std::io::Error> for MatrixError {
     fn from(e: std::io::Error) -> Self {
         MatrixError::IOError(format!("{}", e))
     }
 }
 
+const SPECTRAL_NORM_ITERATIONS: usize = 1000;
+const SPECTRAL_NORM_EPSILON: f32 = 1e-6;
+const EXP_TAYLOR_TERMS: usize = 12;
+
 #[derive(Clone, Debug)]
 pub struct Matrix<T>
 where
@@ -56,7 +125,7 @@ where
 {
     pub fn new(width: usize, height: usize) -> Self {
         let mut elems = Vec::with_capacity(width * height);
-        elems.resize(width * height, 0.0.into());
+        elems.resize(width * height, T::zero());
 
         Self {
             elems,
@@ -65,8 +134,28 @@ where
         }
     }
 
+    pub fn zeros(width: usize, height: usize) -> Self {
+        Self::new(width, height)
+    }
+
+    pub fn ones(width: usize, height: usize) -> Self {
+        Self {
+            elems: vec![T::one(); width * height],
+            width,
+            height,
+        }
+    }
+
+    /// A `width == 0` matrix can only be built from no elements, and then has
+    /// height 0 too.
     pub fn from_vec(elems: Vec<T>, width: usize) -> Result<Self, MatrixError> {
-        if elems.len() % width != 0 {
+        if width == 0 {
+            if elems.is_empty() {
+                Ok(Self::new(0, 0))
+            } else {
+                Err(MatrixError::SizeMismatch)
+            }
+        } else if !elems.len().is_multiple_of(width) {
             Err(MatrixError::SizeMismatch)
         } else {
             Ok(Self {
@@ -77,11 +166,28 @@ where
         }
     }
 
+    pub fn from_rows(rows: &[Vec<T>]) -> Result<Self, MatrixError> {
+        let width = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(MatrixError::SizeMismatch);
+        }
+
+        Ok(Self {
+            elems: rows.concat(),
+            width,
+            height: rows.len(),
+        })
+    }
+
+    pub fn from_columns(cols: &[Vec<T>]) -> Result<Self, MatrixError> {
+        Ok(Self::from_rows(cols)?.transpose())
+    }
+
     pub fn identity(width: usize) -> Self {
         let mut elems = Vec::with_capacity(width * width);
-        elems.resize(width * width, 0.0.into());
+        elems.resize(width * width, T::zero());
         for i in 0..width {
-            elems[i * width + i] = 1.0.into();
+            elems[i * width + i] = T::one();
         }
         Self {
             elems,
@@ -100,6 +206,55 @@ where
         }
     }
 
+    /// One row `[1, x, x², ..., x^degree]` per node.
+    pub fn vandermonde(nodes: &[T], degree: usize) -> Self {
+        let mut elems = Vec::with_capacity(nodes.len() * (degree + 1));
+        for x in nodes {
+            let mut power: T = 1.0.into();
+            for _ in 0..degree {
+                let next = &power * x;
+                elems.push(power);
+                power = next;
+            }
+            elems.push(power);
+        }
+
+        Self {
+            elems,
+            width: degree + 1,
+            height: nodes.len(),
+        }
+    }
+
+    /// The matrix that is constant along every diagonal, with `a_ij` taken from
+    /// `first_row[j - i]` on and above the main diagonal and from
+    /// `first_col[i - j]` below it. The two have to agree on `a_00`.
+    pub fn toeplitz(first_col: &[T], first_row: &[T]) -> Result<Self, MatrixError> {
+        if let (Some(c), Some(r)) = (first_col.first(), first_row.first()) {
+            if c != r {
+                return Err(MatrixError::ToeplitzCornerMismatch);
+            }
+        }
+
+        let (height, width) = (first_col.len(), first_row.len());
+        let mut elems = Vec::with_capacity(width * height);
+        for i in 0..height {
+            for j in 0..width {
+                elems.push(if j >= i {
+                    first_row[j - i].clone()
+                } else {
+                    first_col[i - j].clone()
+                });
+            }
+        }
+
+        Ok(Self {
+            elems,
+            width,
+            height,
+        })
+    }
+
     #[inline(always)]
     pub fn get(&self, row: usize, column: usize) -> &T {
         &self.elems[row * self.width + column]
@@ -110,6 +265,16 @@ where
         self.elems[row * self.width + column] = val;
     }
 
+    /// `self` if it is square, `NotSquare` otherwise. Lets algorithms that need
+    /// a square matrix state that with a single `mat.as_square()?`.
+    pub fn as_square(&self) -> Result<&Self, MatrixError> {
+        if self.width != self.height {
+            Err(MatrixError::NotSquare)
+        } else {
+            Ok(self)
+        }
+    }
+
     #[inline(always)]
     pub fn width(&self) -> usize {
         self.width
@@ -124,6 +289,18 @@ where
         &self.elems
     }
 
+    /// The row-major elements, for changing entries in place. The shape stays
+    /// the same.
+    pub fn as_slice_mut(&mut self) -> &mut [T] {
+        &mut self.elems
+    }
+
+    /// The row-major elements with `(width, height)`, the inverse of
+    /// [`Matrix::from_vec`].
+    pub fn into_raw(self) -> (Vec<T>, usize, usize) {
+        (self.elems, self.width, self.height)
+    }
+
     pub fn transpose(&self) -> Self {
         let mut a = Vec::with_capacity(self.width * self.height);
         a.resize(self.width * self.height, 0.0.into());
@@ -134,7 +311,23 @@ where
             }
         }
 
-        Matrix::from_vec(a, self.height).unwrap()
+        Matrix {
+            elems: a,
+            width: self.height,
+            height: self.width,
+        }
+    }
+
+    /// Transposes a square matrix without allocating.
+    pub fn transpose_in_place(&mut self) -> Result<(), MatrixError> {
+        self.as_square()?;
+
+        for i in 0..self.height {
+            for j in i + 1..self.width {
+                self.elems.swap(i * self.width + j, j * self.width + i);
+            }
+        }
+        Ok(())
     }
 
     pub fn hermetian_transpose(&self) -> Self {
@@ -147,9 +340,14 @@ where
             }
         }
 
-        Matrix::from_vec(a, self.height).unwrap()
+        Matrix {
+            elems: a,
+            width: self.height,
+            height: self.width,
+        }
     }
 
+    /// Sum of the squared magnitudes of all elements, `|z|²` for complex ones.
     pub fn norm_squared(&self) -> f32 {
         let mut sum = 0.0;
         for i in 0..self.width * self.height {
@@ -158,10 +356,89 @@ where
         sum
     }
 
-    pub fn norm(&self) -> f32 {
+    /// Frobenius norm: the square root of the sum of squared element magnitudes.
+    pub fn frobenius_norm(&self) -> f32 {
         self.norm_squared().sqrt()
     }
 
+    /// Alias for [`Matrix::frobenius_norm`], not the operator norm.
+    pub fn norm(&self) -> f32 {
+        self.frobenius_norm()
+    }
+
+    /// Euclidean norm of row `r`, without copying it out.
+    pub fn row_norm(&self, r: usize) -> f32 {
+        self.elems[r * self.width..(r + 1) * self.width]
+            .iter()
+            .map(|x| x.norm_squared())
+            .sum::<f32>()
+            .sqrt()
+    }
+
+    /// Euclidean norm of column `c`, without copying it out.
+    pub fn col_norm(&self, c: usize) -> f32 {
+        (0..self.height)
+            .map(|i| self.get(i, c).norm_squared())
+            .sum::<f32>()
+            .sqrt()
+    }
+
+    /// Spectral (operator 2-) norm: the largest singular value, estimated by
+    /// power iteration on `AᴴA`.
+    pub fn spectral_norm(&self) -> Result<f32, MatrixError> {
+        self.spectral_norm_with_options(SPECTRAL_NORM_ITERATIONS, SPECTRAL_NORM_EPSILON)
+    }
+
+    /// [`Matrix::spectral_norm`] stopping once the Rayleigh quotient `vᴴAᴴAv`
+    /// changes by at most `tol` relative to itself, or giving `DidNotConverge`
+    /// after `max_iters` steps. The quotient never leaves the range of
+    /// eigenvalues of `AᴴA`, so when the top two singular values are close the
+    /// iteration may stop early, but the estimate still lies between them.
+    pub fn spectral_norm_with_options(
+        &self,
+        max_iters: usize,
+        tol: f32,
+    ) -> Result<f32, MatrixError> {
+        let gram = (self.hermetian_transpose() * self)?;
+        // an uneven start vector is unlikely to be orthogonal to the dominant one
+        let mut v = Matrix::from_vec(
+            (0..gram.width).map(|i| T::from((i + 1) as f32)).collect(),
+            1,
+        )?;
+        v = &v / T::from(v.norm());
+
+        let mut eigenvalue = 0.0;
+        let mut change = f32::INFINITY;
+        for _ in 0..max_iters {
+            let w = gram.mul_vec(&v)?;
+            let len = w.norm();
+            if len == 0.0 {
+                return Ok(0.0);
+            }
+            let rayleigh = v.dot(&w)?.norm();
+            v = &w / T::from(len);
+
+            change = (rayleigh - eigenvalue).abs() / rayleigh;
+            eigenvalue = rayleigh;
+            if change <= tol {
+                return Ok(eigenvalue.sqrt());
+            }
+        }
+
+        Err(MatrixError::DidNotConverge {
+            iterations: max_iters,
+            residual: change,
+        })
+    }
+
+    /// `∥QᴴQ - I∥`, how far the columns of `self` are from orthonormal.
+    pub fn orthogonality_error(&self) -> f32 {
+        mul_mat(&self.hermetian_transpose(), self)
+            .and_then(|gram| gram - Matrix::identity(self.width))
+            .expect("QᴴQ is always width x width")
+            .norm()
+    }
+
     pub fn row(&self, row: usize) -> Self {
         let mut elems = Vec::with_capacity(self.width);
         for i in 0..self.width {
@@ -187,6 +464,533 @@ where
             height: self.height,
         }
     }
+
+    pub fn rows(&self) -> impl Iterator<Item = Self> + '_ {
+        (0..self.height).map(|i| self.row(i))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = Self> + '_ {
+        (0..self.width).map(|j| self.column(j))
+    }
+
+    pub fn map<F: Fn(&T) -> T>(&self, f: F) -> Self {
+        Self {
+            elems: self.elems.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn zip_with<F: Fn(&T, &T) -> T>(&self, other: &Self, f: F) -> Result<Self, MatrixError> {
+        if self.width != other.width || self.height != other.height {
+            return Err(MatrixError::SizeMismatch);
+        }
+
+        Ok(Self {
+            elems: self
+                .elems
+                .iter()
+                .zip(other.elems.iter())
+                .map(|(a, b)| f(a, b))
+                .collect(),
+            width: self.width,
+            height: self.height,
+        })
+    }
+
+    pub fn scale(&self, factor: &T) -> Self {
+        self.map(|x| factor * x)
+    }
+
+    /// `numerator / a_ij` for every nonzero entry; zero entries stay zero, so
+    /// for a diagonal matrix `D` this gives `numerator·D⁻¹`.
+    pub fn recip_scaled(&self, numerator: &T) -> Self {
+        let zero: T = 0.0.into();
+        self.map(|x| {
+            if x == &zero {
+                zero.clone()
+            } else {
+                numerator / x
+            }
+        })
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.elems.fill(value);
+    }
+
+    /// Replaces every `a_ii` with `f(i, a_ii)`. Rectangular matrices have
+    /// `min(width, height)` diagonal entries.
+    pub fn apply_diagonal<F: Fn(usize, &T) -> T>(&mut self, f: F) {
+        for i in 0..self.width.min(self.height) {
+            let index = i * self.width + i;
+            self.elems[index] = f(i, &self.elems[index]);
+        }
+    }
+
+    /// `A - μI` in place.
+    pub fn shift_diagonal(&mut self, mu: &T) {
+        self.apply_diagonal(|_, a| a - mu);
+    }
+
+    pub fn hadamard(&self, other: &Self) -> Result<Self, MatrixError> {
+        self.zip_with(other, |a, b| a * b)
+    }
+
+    /// Places `other` to the right of `self`, e.g. to build `[A | b]`.
+    pub fn hstack(&self, other: &Self) -> Result<Self, MatrixError> {
+        if self.height != other.height {
+            return Err(MatrixError::SizeMismatch);
+        }
+
+        let width = self.width + other.width;
+        let mut elems = Vec::with_capacity(width * self.height);
+        for i in 0..self.height {
+            elems.extend_from_slice(&self.elems[i * self.width..(i + 1) * self.width]);
+            elems.extend_from_slice(&other.elems[i * other.width..(i + 1) * other.width]);
+        }
+
+        Ok(Self {
+            elems,
+            width,
+            height: self.height,
+        })
+    }
+
+    /// Places `other` below `self`.
+    pub fn vstack(&self, other: &Self) -> Result<Self, MatrixError> {
+        if self.width != other.width {
+            return Err(MatrixError::SizeMismatch);
+        }
+
+        Ok(Self {
+            elems: [self.elems.as_slice(), other.elems.as_slice()].concat(),
+            width: self.width,
+            height: self.height + other.height,
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn pow(&self, exp: u32) -> Result<Self, MatrixError> {
+        self.as_square()?;
+
+        let mut res = Self::identity(self.width);
+        let mut base = self.clone();
+        let mut exp = exp;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = (&res * &base)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = (&base * &base)?;
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Inner product of two column vectors, conjugating `self`.
+    pub fn dot(&self, other: &Matrix<T>) -> Result<T, MatrixError> {
+        if self.width != 1 || other.width != 1 || self.height != other.height {
+            return Err(MatrixError::SizeMismatch);
+        }
+
+        let mut sum = T::zero();
+        for (a, b) in self.elems.iter().zip(other.elems.iter()) {
+            sum = sum + &a.conjugate() * b;
+        }
+        Ok(sum)
+    }
+
+    /// Outer product `self * otherᴴ`, treating both operands as vectors.
+    pub fn outer(&self, other: &Matrix<T>) -> Matrix<T> {
+        let mut elems = Vec::with_capacity(self.elems.len() * other.elems.len());
+        for a in &self.elems {
+            for b in &other.elems {
+                elems.push(a * &b.conjugate());
+            }
+        }
+
+        Self {
+            elems,
+            width: other.elems.len(),
+            height: self.elems.len(),
+        }
+    }
+
+    /// Whether `a_ij == a_ji` within `epsilon`.
+    pub fn is_symmetric(&self, epsilon: f32) -> bool {
+        self.is_close_to_transpose(epsilon, |x| x.clone())
+    }
+
+    /// Whether `conj(a_ij) == a_ji` within `epsilon`. For real matrices this is
+    /// the same as [`Matrix::is_symmetric`].
+    pub fn is_hermitian(&self, epsilon: f32) -> bool {
+        self.is_close_to_transpose(epsilon, |x| x.conjugate())
+    }
+
+    /// Square, with every entry within `epsilon` of the identity's.
+    pub fn is_identity(&self, epsilon: f32) -> bool {
+        if self.width != self.height {
+            return false;
+        }
+
+        let one = T::one();
+        for i in 0..self.height {
+            for j in 0..self.width {
+                let x = self.get(i, j);
+                let diff = if i == j { (x - &one).norm() } else { x.norm() };
+                if diff > epsilon {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Every entry below the diagonal is within `epsilon` of zero. Rectangular
+    /// matrices are allowed, like the `R` of a QR factorization.
+    pub fn is_upper_triangular(&self, epsilon: f32) -> bool {
+        (0..self.height).all(|i| (0..i.min(self.width)).all(|j| self.get(i, j).norm() <= epsilon))
+    }
+
+    /// Every entry above the diagonal is within `epsilon` of zero.
+    pub fn is_lower_triangular(&self, epsilon: f32) -> bool {
+        (0..self.height).all(|i| (i + 1..self.width).all(|j| self.get(i, j).norm() <= epsilon))
+    }
+
+    /// `(lower, upper)`: how far below and above the diagonal the farthest
+    /// entry with `|a_ij| > epsilon` lies. A tridiagonal matrix gives `(1, 1)`.
+    pub fn bandwidth(&self, epsilon: f32) -> (usize, usize) {
+        let mut lower = 0;
+        let mut upper = 0;
+        for i in 0..self.height {
+            for j in 0..self.width {
+                if self.get(i, j).norm() > epsilon {
+                    if i > j {
+                        lower = lower.max(i - j);
+                    } else {
+                        upper = upper.max(j - i);
+                    }
+                }
+            }
+        }
+        (lower, upper)
+    }
+
+    fn is_close_to_transpose<F: Fn(&T) -> T>(&self, epsilon: f32, f: F) -> bool {
+        if self.width != self.height {
+            return false;
+        }
+
+        for i in 0..self.height {
+            for j in i..self.width {
+                if (&f(self.get(i, j)) - self.get(j, i)).norm() > epsilon {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// `∥A - Aᵀ∥`, zero exactly when `self` is symmetric. Infinite for a
+    /// matrix that is not square.
+    pub fn symmetry_error(&self) -> f32 {
+        self.transpose_error(|x| x.clone())
+    }
+
+    /// `∥A - Aᴴ∥`, the same as [`Matrix::symmetry_error`] for real matrices.
+    pub fn hermitian_error(&self) -> f32 {
+        self.transpose_error(|x| x.conjugate())
+    }
+
+    fn transpose_error<F: Fn(&T) -> T>(&self, f: F) -> f32 {
+        if self.width != self.height {
+            return f32::INFINITY;
+        }
+
+        let mut sum = 0.0;
+        for i in 0..self.height {
+            for j in 0..self.width {
+                sum += (self.get(i, j) - &f(self.get(j, i))).norm_squared();
+            }
+        }
+        sum.sqrt()
+    }
+
+    /// Gershgorin discs `(center, radius)` per row: every eigenvalue lies in
+    /// at least one of them.
+    pub fn gershgorin_discs(&self) -> Vec<(T, f32)> {
+        let n = usize::min(self.width, self.height);
+        let mut discs = Vec::with_capacity(n);
+        for i in 0..n {
+            let mut radius = 0.0;
+            for j in 0..self.width {
+                if j != i {
+                    radius += self.get(i, j).norm();
+                }
+            }
+            discs.push((self.get(i, i).clone(), radius));
+        }
+        discs
+    }
+
+    /// Solves `self * x = b` by back substitution, reading only the upper
+    /// triangle of `self`. Each column of `b` is solved for separately.
+    pub fn solve_upper_triangular(&self, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        self.check_triangular_system(b)?;
+
+        let n = self.width;
+        let mut x = Matrix::new(b.width, n);
+        for c in 0..b.width {
+            for i in (0..n).rev() {
+                let mut xi = b.get(i, c).clone();
+                for j in i + 1..n {
+                    xi = xi - self.get(i, j) * x.get(j, c);
+                }
+                x.set(i, c, &xi / self.get(i, i));
+            }
+        }
+
+        Ok(x)
+    }
+
+    /// Solves `self * x = b` by forward substitution, reading only the lower
+    /// triangle of `self`. Each column of `b` is solved for separately.
+    pub fn solve_lower_triangular(&self, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        self.check_triangular_system(b)?;
+
+        let n = self.width;
+        let mut x = Matrix::new(b.width, n);
+        for c in 0..b.width {
+            for i in 0..n {
+                let mut xi = b.get(i, c).clone();
+                for j in 0..i {
+                    xi = xi - self.get(i, j) * x.get(j, c);
+                }
+                x.set(i, c, &xi / self.get(i, i));
+            }
+        }
+
+        Ok(x)
+    }
+
+    fn check_triangular_system(&self, b: &Matrix<T>) -> Result<(), MatrixError> {
+        self.as_square()?;
+        if b.height != self.height {
+            return Err(MatrixError::SizeMismatch);
+        }
+
+        let zero: T = 0.0.into();
+        for i in 0..self.width {
+            if self.get(i, i) == &zero {
+                return Err(MatrixError::NotRegular);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn mul_vec(&self, v: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        if v.width != 1 || self.width != v.height {
+            return Err(MatrixError::SizeMismatch);
+        }
+
+        let mut elems = Vec::with_capacity(self.height);
+        for i in 0..self.height {
+            let row = &self.elems[i * self.width..(i + 1) * self.width];
+            let mut sum = T::zero();
+            for (a, x) in row.iter().zip(v.elems.iter()) {
+                sum = sum + a * x;
+            }
+            elems.push(sum);
+        }
+
+        Matrix::from_vec(elems, 1)
+    }
+}
+
+fn kahan_sum<I: Iterator<Item = f32>>(values: I) -> f32 {
+    let mut sum = 0.0;
+    let mut compensation = 0.0;
+    for x in values {
+        let y = x - compensation;
+        let t = sum + y;
+        compensation = (t - sum) - y;
+        sum = t;
+    }
+    sum
+}
+
+impl Matrix<f32> {
+    /// Matrix exponential by scaling and squaring: `e^A = (e^(A / 2^s))^(2^s)`,
+    /// with `s` chosen so the Taylor series of the scaled matrix converges fast.
+    #[allow(dead_code)]
+    pub fn exp(&self) -> Result<Matrix<f32>, MatrixError> {
+        self.as_square()?;
+
+        // the Frobenius norm bounds the spectral one, so ‖A / 2^s‖ <= 1/2
+        let squarings = (self.norm() / 0.5).log2().ceil().clamp(0.0, 128.0) as i32;
+        let scaled = self / 2f32.powi(squarings);
+
+        let mut res = Matrix::identity(self.width);
+        let mut term = Matrix::identity(self.width);
+        for k in 1..=EXP_TAYLOR_TERMS {
+            term = (term * &scaled)? / k as f32;
+            res = (res + &term)?;
+        }
+
+        for _ in 0..squarings {
+            res = (&res * &res)?;
+        }
+        Ok(res)
+    }
+
+    /// `self * other` with the rows of the result split across scoped threads,
+    /// one block per available core. Same result as `Mul`, which stays serial.
+    #[allow(dead_code)]
+    pub fn par_mul(&self, other: &Matrix<f32>) -> Result<Matrix<f32>, MatrixError> {
+        if self.width != other.height {
+            return Err(MatrixError::SizeMismatch);
+        }
+
+        let width = other.width;
+        let mut c = vec![0.0; width * self.height];
+        if c.is_empty() {
+            return Ok(Matrix::new(width, self.height));
+        }
+
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let rows_per_thread = self.height.div_ceil(threads);
+        std::thread::scope(|scope| {
+            for (block, c_block) in c.chunks_mut(rows_per_thread * width).enumerate() {
+                let first_row = block * rows_per_thread;
+                scope.spawn(move || {
+                    for (r, c_row) in c_block.chunks_mut(width).enumerate() {
+                        let a_row = self.row_slice(first_row + r);
+                        for (k, a_ik) in a_row.iter().enumerate() {
+                            let b_row = other.row_slice(k);
+                            for (c_ij, b_kj) in c_row.iter_mut().zip(b_row.iter()) {
+                                *c_ij += a_ik * b_kj;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(Matrix {
+            elems: c,
+            width,
+            height: self.height,
+        })
+    }
+
+    fn row_slice(&self, row: usize) -> &[f32] {
+        &self.elems[row * self.width..(row + 1) * self.width]
+    }
+
+    /// `1.0` where `a_ij >= threshold`, `0.0` elsewhere.
+    #[allow(dead_code)]
+    pub fn ge_mask(&self, threshold: f32) -> Matrix<f32> {
+        self.map(|&x| if x >= threshold { 1.0 } else { 0.0 })
+    }
+
+    /// `1.0` where `a_ij <= threshold`, `0.0` elsewhere.
+    #[allow(dead_code)]
+    pub fn le_mask(&self, threshold: f32) -> Matrix<f32> {
+        self.map(|&x| if x <= threshold { 1.0 } else { 0.0 })
+    }
+
+    /// Inner product of two column vectors with Kahan-compensated summation.
+    #[allow(dead_code)]
+    pub fn dot_compensated(&self, other: &Matrix<f32>) -> Result<f32, MatrixError> {
+        if self.width != 1 || other.width != 1 || self.height != other.height {
+            return Err(MatrixError::SizeMismatch);
+        }
+        Ok(kahan_sum(
+            self.elems
+                .iter()
+                .zip(other.elems.iter())
+                .map(|(a, b)| a * b),
+        ))
+    }
+
+    /// Frobenius norm with Kahan-compensated summation of the squares. Slower
+    /// than [`Matrix::norm`], but does not drop small entries next to big ones.
+    #[allow(dead_code)]
+    pub fn norm_compensated(&self) -> f32 {
+        kahan_sum(self.elems.iter().map(|x| x * x)).sqrt()
+    }
+
+    /// Reduced row echelon form, found by Gauss-Jordan elimination with
+    /// partial pivoting, together with the pivot columns in increasing order.
+    /// Entries with `|x| <= epsilon` are treated as zero.
+    #[allow(dead_code)]
+    pub fn rref(&self, epsilon: f32) -> (Matrix<f32>, Vec<usize>) {
+        let mut res = self.clone();
+        let mut pivots = Vec::new();
+
+        for col in 0..self.width {
+            let row = pivots.len();
+            if row == self.height {
+                break;
+            }
+
+            let pivot = (row..self.height)
+                .max_by(|&a, &b| res.get(a, col).abs().total_cmp(&res.get(b, col).abs()))
+                .unwrap();
+            if res.get(pivot, col).abs() <= epsilon {
+                for i in row..self.height {
+                    res.set(i, col, 0.0);
+                }
+                continue;
+            }
+
+            for j in 0..self.width {
+                res.elems.swap(row * self.width + j, pivot * self.width + j);
+            }
+
+            let p = *res.get(row, col);
+            for j in col..self.width {
+                res.set(row, j, res.get(row, j) / p);
+            }
+
+            for i in 0..self.height {
+                let factor = *res.get(i, col);
+                if i == row || factor == 0.0 {
+                    continue;
+                }
+                for j in col..self.width {
+                    res.set(i, j, res.get(i, j) - factor * res.get(row, j));
+                }
+                res.set(i, col, 0.0);
+            }
+
+            pivots.push(col);
+        }
+
+        (res, pivots)
+    }
+
+    /// A basis of the kernel of `self` as column vectors, one for each free
+    /// column of the [`rref`](Self::rref).
+    #[allow(dead_code)]
+    pub fn null_space(&self, epsilon: f32) -> Vec<Matrix<f32>> {
+        let (r, pivots) = self.rref(epsilon);
+
+        (0..self.width)
+            .filter(|col| !pivots.contains(col))
+            .map(|free| {
+                let mut v = Matrix::new(1, self.width);
+                v.set(free, 0, 1.0);
+                for (row, &pivot) in pivots.iter().enumerate() {
+                    v.set(pivot, 0, -r.get(row, free));
+                }
+                v
+            })
+            .collect()
+    }
 }
 
 impl<T> Display for Matrix<T>
@@ -210,6 +1014,35 @@ where
     }
 }
 
+fn mul_mat<T>(a: &Matrix<T>, b: &Matrix<T>) -> Result<Matrix<T>, MatrixError>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    if a.width != b.height {
+        return Err(MatrixError::SizeMismatch);
+    }
+    let mut c = vec![T::zero(); b.width * a.height];
+
+    // i, k, j order keeps the inner loop unit-stride over both `c` and `b`
+    for i in 0..a.height {
+        let c_row = &mut c[i * b.width..(i + 1) * b.width];
+        for k in 0..a.width {
+            let a_ik = &a.elems[i * a.width + k];
+            let b_row = &b.elems[k * b.width..(k + 1) * b.width];
+            for (c_ij, b_kj) in c_row.iter_mut().zip(b_row.iter()) {
+                c_ij.mul_add_assign(a_ik, b_kj);
+            }
+        }
+    }
+
+    Ok(Matrix {
+        elems: c,
+        width: b.width,
+        height: a.height,
+    })
+}
+
 impl<T> Add<Matrix<T>> for Matrix<T>
 where
     T: NumNonRef,
@@ -230,7 +1063,11 @@ where
             c[i] = &a[i] + &b[i];
         }
 
-        Ok(Matrix::from_vec(c, self.width)?)
+        Ok(Matrix {
+            elems: c,
+            width: self.width,
+            height: self.height,
+        })
     }
 }
 
@@ -254,7 +1091,11 @@ where
             c[i] = &a[i] - &b[i];
         }
 
-        Ok(Matrix::from_vec(c, self.width)?)
+        Ok(Matrix {
+            elems: c,
+            width: self.width,
+            height: self.height,
+        })
     }
 }
 
@@ -266,24 +1107,7 @@ where
     type Output = Result<Matrix<T>, MatrixError>;
 
     fn mul(self, rhs: Matrix<T>) -> Self::Output {
-        if self.width != rhs.height {
-            return Err(MatrixError::SizeMismatch);
-        }
-        let mut c = Vec::with_capacity(rhs.width * self.height);
-        c.resize(rhs.width * self.height, 0.0.into());
-        let a = self.elems_raw();
-        let b = rhs.elems_raw();
-
-        for i in 0..self.height {
-            for j in 0..rhs.width {
-                for k in 0..self.width {
-                    c[i * rhs.width + j] =
-                        &c[i * rhs.width + j] + &a[i * self.width + k] * &b[k * rhs.width + j];
-                }
-            }
-        }
-
-        Ok(Matrix::from_vec(c, rhs.width)?)
+        mul_mat(&self, &rhs)
     }
 }
 
@@ -307,7 +1131,11 @@ where
             c[i] = &a[i] + &b[i];
         }
 
-        Ok(Matrix::from_vec(c, self.width)?)
+        Ok(Matrix {
+            elems: c,
+            width: self.width,
+            height: self.height,
+        })
     }
 }
 
@@ -331,7 +1159,11 @@ where
             c[i] = &a[i] - &b[i];
         }
 
-        Ok(Matrix::from_vec(c, self.width)?)
+        Ok(Matrix {
+            elems: c,
+            width: self.width,
+            height: self.height,
+        })
     }
 }
 
@@ -343,24 +1175,7 @@ where
     type Output = Result<Matrix<T>, MatrixError>;
 
     fn mul(self, rhs: &Matrix<T>) -> Self::Output {
-        if self.width != rhs.height {
-            return Err(MatrixError::SizeMismatch);
-        }
-        let mut c = Vec::with_capacity(rhs.width * self.height);
-        c.resize(rhs.width * self.height, 0.0.into());
-        let a = self.elems_raw();
-        let b = rhs.elems_raw();
-
-        for i in 0..self.height {
-            for j in 0..rhs.width {
-                for k in 0..self.width {
-                    c[i * rhs.width + j] =
-                        &c[i * rhs.width + j] + &a[i * self.width + k] * &b[k * rhs.width + j];
-                }
-            }
-        }
-
-        Ok(Matrix::from_vec(c, rhs.width)?)
+        mul_mat(&self, rhs)
     }
 }
 
@@ -384,7 +1199,11 @@ where
             c[i] = &a[i] + &b[i];
         }
 
-        Ok(Matrix::from_vec(c, self.width)?)
+        Ok(Matrix {
+            elems: c,
+            width: self.width,
+            height: self.height,
+        })
     }
 }
 
@@ -408,7 +1227,11 @@ where
             c[i] = &a[i] - &b[i];
         }
 
-        Ok(Matrix::from_vec(c, self.width)?)
+        Ok(Matrix {
+            elems: c,
+            width: self.width,
+            height: self.height,
+        })
     }
 }
 
@@ -420,24 +1243,7 @@ where
     type Output = Result<Matrix<T>, MatrixError>;
 
     fn mul(self, rhs: Matrix<T>) -> Self::Output {
-        if self.width != rhs.height {
-            return Err(MatrixError::SizeMismatch);
-        }
-        let mut c = Vec::with_capacity(rhs.width * self.height);
-        c.resize(rhs.width * self.height, 0.0.into());
-        let a = self.elems_raw();
-        let b = rhs.elems_raw();
-
-        for i in 0..self.height {
-            for j in 0..rhs.width {
-                for k in 0..self.width {
-                    c[i * rhs.width + j] =
-                        &c[i * rhs.width + j] + &a[i * self.width + k] * &b[k * rhs.width + j];
-                }
-            }
-        }
-
-        Ok(Matrix::from_vec(c, rhs.width)?)
+        mul_mat(self, &rhs)
     }
 }
 
@@ -461,7 +1267,11 @@ where
             c[i] = &a[i] + &b[i];
         }
 
-        Ok(Matrix::from_vec(c, self.width)?)
+        Ok(Matrix {
+            elems: c,
+            width: self.width,
+            height: self.height,
+        })
     }
 }
 
@@ -485,7 +1295,11 @@ where
             c[i] = &a[i] - &b[i];
         }
 
-        Ok(Matrix::from_vec(c, self.width)?)
+        Ok(Matrix {
+            elems: c,
+            width: self.width,
+            height: self.height,
+        })
     }
 }
 
@@ -497,24 +1311,7 @@ where
     type Output = Result<Matrix<T>, MatrixError>;
 
     fn mul(self, rhs: &Matrix<T>) -> Self::Output {
-        if self.width != rhs.height {
-            return Err(MatrixError::SizeMismatch);
-        }
-        let mut c = Vec::with_capacity(rhs.width * self.height);
-        c.resize(rhs.width * self.height, 0.0.into());
-        let a = self.elems_raw();
-        let b = rhs.elems_raw();
-
-        for i in 0..self.height {
-            for j in 0..rhs.width {
-                for k in 0..self.width {
-                    c[i * rhs.width + j] =
-                        &c[i * rhs.width + j] + &a[i * self.width + k] * &b[k * rhs.width + j];
-                }
-            }
-        }
-
-        Ok(Matrix::from_vec(c, rhs.width)?)
+        mul_mat(self, rhs)
     }
 }
 
@@ -531,7 +1328,11 @@ where
             elems.push(&rhs * &self.elems[i]);
         }
 
-        Matrix::from_vec(elems, self.width).unwrap()
+        Matrix {
+            elems,
+            width: self.width,
+            height: self.height,
+        }
     }
 }
 
@@ -548,7 +1349,62 @@ where
             elems.push(&rhs * &self.elems[i]);
         }
 
-        Matrix::from_vec(elems, self.width).unwrap()
+        Matrix {
+            elems,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T> Neg for Matrix<T>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    type Output = Matrix<T>;
+
+    fn neg(mut self) -> Self::Output {
+        for x in self.elems.iter_mut() {
+            *x = -x.clone();
+        }
+        self
+    }
+}
+
+impl<T> Neg for &Matrix<T>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    type Output = Matrix<T>;
+
+    fn neg(self) -> Self::Output {
+        self.map(|x| -x.clone())
+    }
+}
+
+impl<T> Mul<Matrix<T>> for f32
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    type Output = Matrix<T>;
+
+    fn mul(self, rhs: Matrix<T>) -> Self::Output {
+        rhs.scale(&self.into())
+    }
+}
+
+impl<T> Mul<&Matrix<T>> for f32
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    type Output = Matrix<T>;
+
+    fn mul(self, rhs: &Matrix<T>) -> Self::Output {
+        rhs.scale(&self.into())
     }
 }
 
@@ -565,7 +1421,11 @@ where
             elems.push(&self.elems[i] / &rhs);
         }
 
-        Matrix::from_vec(elems, self.width).unwrap()
+        Matrix {
+            elems,
+            width: self.width,
+            height: self.height,
+        }
     }
 }
 
@@ -582,6 +1442,10 @@ where
             elems.push(&self.elems[i] / &rhs);
         }
 
-        Matrix::from_vec(elems, self.width).unwrap()
+        Matrix {
+            elems,
+            width: self.width,
+            height: self.height,
+        }
     }
 }
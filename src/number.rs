@@ -22,6 +22,32 @@ pub trait NumNonRef:
     }
     fn conjugate(&self) -> Self;
     fn absolute(&self) -> Self;
+
+    /// The additive identity, for types with something cheaper than `From<f32>`
+    /// to override.
+    fn zero() -> Self {
+        0.0.into()
+    }
+
+    /// The multiplicative identity.
+    fn one() -> Self {
+        1.0.into()
+    }
+
+    /// Whether arithmetic on this type never rounds, so that only an exact zero
+    /// should be treated as one.
+    fn is_exact() -> bool {
+        false
+    }
+
+    /// `self = self + a * b`, for types that can accumulate without temporaries
+    /// to override.
+    fn mul_add_assign(&mut self, a: &Self, b: &Self)
+    where
+        for<'a> &'a Self: NumRef<Self>,
+    {
+        *self = &*self + &(a * b);
+    }
 }
 
 pub trait NumRef<T>:
@@ -71,30 +97,35 @@ impl NumRef<Complex> for &Complex {}
 
 impl From<f32> for LongInt {
     fn from(x: f32) -> Self {
-        LongInt::from(x as i32)
+        LongInt::from(x as i64)
     }
 }
 
+/// `rhs` is taken as the exact fraction it represents, the result is truncated.
+/// Panics if `rhs` is NaN or infinite, as no fraction represents it.
 impl Mul<f32> for LongInt {
     type Output = LongInt;
 
     fn mul(self, rhs: f32) -> Self::Output {
-        self * LongInt::from(rhs)
+        let rhs = Fraction::<LongInt>::from(rhs);
+        &(self * rhs.num()) / rhs.den()
     }
 }
+/// Truncating division by the exact fraction `rhs` represents. Panics if `rhs`
+/// is NaN, infinite or zero.
 impl Div<f32> for LongInt {
     type Output = LongInt;
 
     fn div(self, rhs: f32) -> Self::Output {
-        self * LongInt::from(rhs)
+        let rhs = Fraction::<LongInt>::from(rhs);
+        &(self * rhs.den()) / rhs.num()
     }
 }
 
 impl NumNonRef for LongInt {
     fn norm_squared(&self) -> f32 {
-        let c = u32::from_le_bytes([self.get(0), self.get(1), self.get(2), self.get(3)]);
-
-        c as f32
+        let x = f32::from(self);
+        x * x
     }
 
     fn conjugate(&self) -> Self {
@@ -104,6 +135,24 @@ impl NumNonRef for LongInt {
     fn absolute(&self) -> Self {
         self.abs()
     }
+
+    fn zero() -> Self {
+        LongInt::new()
+    }
+
+    fn one() -> Self {
+        let mut one = LongInt::new();
+        one.set(0, 1);
+        one
+    }
+
+    fn is_exact() -> bool {
+        true
+    }
+
+    fn mul_add_assign(&mut self, a: &Self, b: &Self) {
+        LongInt::mul_add_assign(self, a, b)
+    }
 }
 impl NumRef<LongInt> for &LongInt {}
 
@@ -113,15 +162,19 @@ where
     for<'a> &'a T: NumRef<T> + Rem<Output = T>,
 {
     fn norm_squared(&self) -> f32 {
-        todo!()
+        self.num().norm_squared() / self.den().norm_squared()
     }
 
     fn conjugate(&self) -> Self {
-        todo!()
+        self.clone()
     }
 
     fn absolute(&self) -> Self {
-        todo!()
+        Fraction::new(self.den().clone(), self.num().absolute())
+    }
+
+    fn is_exact() -> bool {
+        T::is_exact()
     }
 }
 
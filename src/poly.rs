@@ -3,7 +3,13 @@ use std::{
     ops::{Add, Div, Mul, Sub},
 };
 
-use crate::number::{NumNonRef, NumRef};
+use crate::{
+    complex::Complex,
+    longint::LongInt,
+    lu::gauss_eliminate,
+    matrix::{Matrix, MatrixError},
+    number::{NumNonRef, NumRef},
+};
 
 #[derive(Debug)]
 pub struct Polynome<T>
@@ -56,6 +62,220 @@ where
     pub fn normalize(&self) -> Self {
         self / self.get(self.degree())
     }
+
+    /// `p(x)` by Horner's scheme.
+    pub fn eval(&self, x: &T) -> T {
+        let mut res: T = 0.0.into();
+        for c in self.coefs.iter().rev() {
+            res = &(&res * x) + c;
+        }
+        res
+    }
+
+    /// `|p(λ)|` for every candidate root `λ`.
+    pub fn residuals(&self, roots: &[T]) -> Vec<f32> {
+        roots.iter().map(|x| self.eval(x).norm()).collect()
+    }
+
+    /// Long division, `self = quotient·divisor + remainder` with the remainder
+    /// of lower degree than `divisor`; a vanishing quotient or remainder is
+    /// returned as the zero polynomial. `None` if `divisor` is zero.
+    pub fn div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        let zero = T::zero();
+        let d = divisor.coefs.iter().rposition(|c| c != &zero)?;
+        let lead = &divisor.coefs[d];
+
+        let mut rem = self.coefs.clone();
+        if rem.is_empty() {
+            rem.push(zero.clone());
+        }
+        if rem.len() <= d {
+            return Some((Self::from_coefs(&[zero]), Self { coefs: rem }));
+        }
+
+        let mut quot = vec![zero; rem.len() - d];
+        for k in (0..quot.len()).rev() {
+            let factor = &rem[k + d] / lead;
+            for (j, c) in divisor.coefs[..=d].iter().enumerate() {
+                rem[k + j] = &rem[k + j] - &(&factor * c);
+            }
+            quot[k] = factor;
+        }
+        rem.truncate(d);
+        if rem.is_empty() {
+            rem.push(T::zero());
+        }
+
+        Some((Self { coefs: quot }, Self { coefs: rem }))
+    }
+
+    /// `p'`, the coefficient of `x^k` being `(k + 1)·a_(k+1)`. The derivative
+    /// of a constant is the zero polynomial.
+    pub fn derivative(&self) -> Self {
+        if self.coefs.len() < 2 {
+            return Self::from_coefs(&[T::zero()]);
+        }
+        let coefs: Vec<T> = self
+            .coefs
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(k, c)| c * &T::from(k as f32))
+            .collect();
+        Self { coefs }
+    }
+
+    /// Refines `guess` with up to `iters` Newton steps `x -= p(x) / p'(x)`,
+    /// stopping early at a stationary point or once `x` no longer moves.
+    pub fn polish_root(&self, guess: T, iters: usize) -> T {
+        let derivative = self.derivative();
+        let zero = T::zero();
+        let mut x = guess;
+        for _ in 0..iters {
+            let d = derivative.eval(&x);
+            if d == zero {
+                break;
+            }
+            let step = &self.eval(&x) / &d;
+            if step == zero {
+                break;
+            }
+            x = &x - &step;
+        }
+        x
+    }
+}
+
+impl Polynome<LongInt> {
+    /// The same polynomial with every coefficient converted by `f32::from`.
+    /// That rounds after each base-256 digit, so a coefficient wider than the
+    /// `f32` mantissa does not always come out as the nearest `f32`.
+    #[allow(dead_code)]
+    pub fn to_f32(&self) -> Polynome<f32> {
+        let coefs: Vec<f32> = self.coefs.iter().map(f32::from).collect();
+        Polynome::from_coefs(&coefs)
+    }
+}
+
+impl Polynome<f32> {
+    /// [`Polynome::polish_root`] for a complex root of a real polynomial, such
+    /// as one found by [`Polynome::roots`].
+    #[allow(dead_code)]
+    pub fn polish_complex_root(&self, guess: Complex, iters: usize) -> Complex {
+        let coefs: Vec<Complex> = self.coefs.iter().map(|&c| Complex::from(c)).collect();
+        Polynome::from_coefs(&coefs).polish_root(guess, iters)
+    }
+
+    /// The real roots, one at a time: Newton's method from a few seeds finds a
+    /// root `r` with `|p(r)| <= epsilon`, which is polished against the original
+    /// polynomial and divided out as `(x - r)`. Once no seed converges, the
+    /// real roots left in the deflated polynomial come from [`Polynome::roots`].
+    ///
+    /// Each division leaves rounding error in the remaining coefficients, so
+    /// later roots are less accurate and close or repeated roots may be lost.
+    /// Complex roots are not returned.
+    #[allow(dead_code)]
+    pub fn all_roots_by_deflation(&self, iters: usize, epsilon: f32) -> Vec<f32> {
+        let mut found = Vec::new();
+        let Some(degree) = self.coefs.iter().rposition(|&c| c != 0.0) else {
+            return found;
+        };
+        let mut p = Polynome::from_coefs(&self.coefs[..=degree]);
+
+        while p.coefs.len() > 1 {
+            // every root lies within the Cauchy bound 1 + max |a_k / a_n|
+            let lead = p.coefs[p.coefs.len() - 1];
+            let bound = 1.0 + p.coefs.iter().map(|c| (c / lead).abs()).fold(0.0, f32::max);
+            let root = [0.0, 1.0, -1.0, bound, -bound]
+                .into_iter()
+                .map(|seed| p.polish_root(seed, iters))
+                .find(|r| r.is_finite() && p.eval(r).abs() <= epsilon);
+
+            let Some(root) = root else {
+                if let Ok(rest) = p.roots(iters, epsilon) {
+                    found.extend(rest.iter().filter(|z| z.im.abs() <= epsilon).map(|z| z.re));
+                }
+                break;
+            };
+
+            found.push(self.polish_root(root, iters));
+            // x - root is monic, so the division always succeeds
+            p = p.div_rem(&Polynome::from_coefs(&[-root, 1.0])).unwrap().0;
+        }
+
+        found
+    }
+
+    /// All complex roots at once by the Durand-Kerner iteration
+    /// `z_i -= p(z_i) / Π_{j≠i} (z_i - z_j)`, stopping once no root moves by
+    /// more than `epsilon`.
+    #[allow(dead_code)]
+    pub fn roots(&self, max_iters: usize, epsilon: f32) -> Result<Vec<Complex>, MatrixError> {
+        let Some(degree) = self.coefs.iter().rposition(|&c| c != 0.0) else {
+            return Ok(Vec::new());
+        };
+        let lead = self.coefs[degree];
+        let monic: Vec<Complex> = self.coefs[..=degree]
+            .iter()
+            .map(|&c| Complex::from(c / lead))
+            .collect();
+        let monic = Polynome::from_coefs(&monic);
+
+        // powers of a point off both axes avoid symmetric starting guesses
+        let seed = Complex::new(0.4, 0.9);
+        let mut z = Vec::with_capacity(degree);
+        let mut power = Complex::from(1.0);
+        for _ in 0..degree {
+            z.push(power);
+            power = power * seed;
+        }
+
+        let mut change = f32::INFINITY;
+        for _ in 0..max_iters {
+            change = 0.0;
+            for i in 0..degree {
+                let mut denom = Complex::from(1.0);
+                for j in 0..degree {
+                    if i != j {
+                        denom = denom * (z[i] - z[j]);
+                    }
+                }
+                let step = monic.eval(&z[i]) / denom;
+                z[i] = z[i] - step;
+                change = change.max(step.abs());
+            }
+            if change <= epsilon {
+                return Ok(z);
+            }
+        }
+
+        Err(MatrixError::DidNotConverge {
+            iterations: max_iters,
+            residual: change,
+        })
+    }
+}
+
+/// The polynomial of degree `points.len() - 1` through every `(x, y)`, found
+/// by solving the Vandermonde system for its coefficients. Repeated `x` values
+/// make the system singular and give `NotRegular`.
+#[allow(dead_code)]
+pub fn interpolate(points: &[(f32, f32)]) -> Result<Polynome<f32>, MatrixError> {
+    if points.is_empty() {
+        return Ok(Polynome::from_coefs(&[0.0]));
+    }
+    for (i, (x, _)) in points.iter().enumerate() {
+        if points[..i].iter().any(|(other, _)| other == x) {
+            return Err(MatrixError::NotRegular);
+        }
+    }
+
+    let xs: Vec<_> = points.iter().map(|&(x, _)| x).collect();
+    let ys: Vec<_> = points.iter().map(|&(_, y)| y).collect();
+    let v = Matrix::vandermonde(&xs, points.len() - 1);
+    let coefs = gauss_eliminate(&v, &Matrix::from_vec(ys, 1)?)?;
+
+    Ok(Polynome::from_coefs(coefs.elems_raw()))
 }
 
 impl<T> Display for Polynome<T>
@@ -1,13 +1,33 @@
-use std::{fs::File, path::PathBuf, time::Instant};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 use crate::{
     io::{read_mat, write_mat_complex, write_mat_f32, Either, QRMethod},
+    lu::SolveReport,
     matrix::{Matrix, MatrixError},
     measure,
     number::{NumNonRef, NumRef},
 };
 
+const DEPENDENT_COLUMN_EPSILON: f32 = 1e-6;
+const MAX_REORTHOGONALIZATIONS: usize = 10;
+
+/// QR decomposition by Householder reflections. The strict lower triangle of
+/// `R` is set to exactly zero afterwards instead of keeping the rounding noise.
 pub fn qr_householder<T>(mat: &Matrix<T>) -> Result<(Matrix<T>, Matrix<T>), MatrixError>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    householder(mat).map(|(q, r, _)| (q, r))
+}
+
+/// [`qr_householder`] that also counts the reflections applied. Columns that
+/// are already zero below the diagonal are skipped.
+fn householder<T>(mat: &Matrix<T>) -> Result<(Matrix<T>, Matrix<T>, usize), MatrixError>
 where
     T: NumNonRef,
     for<'a> &'a T: NumRef<T>,
@@ -19,6 +39,7 @@ where
 
     let mut r = mat.clone();
     let mut q = Matrix::identity(width);
+    let mut reflections = 0;
 
     for layer in 0..width {
         let mut column_norm = 0.0;
@@ -35,17 +56,44 @@ where
                 0,
                 &a + a.clone() / a.norm().into() * column_norm.sqrt().into(),
             );
+        } else {
+            // a zero on the diagonal has no phase, any unit one will do
+            v.set(layer, 0, column_norm.sqrt().into());
         }
         for i in layer + 1..width {
             v.set(i, 0, r.get(i, layer).clone());
         }
+        if v.norm() == 0.0 {
+            // the column is already zero from the diagonal down
+            continue;
+        }
         v = &v / v.norm().into();
 
         mirror_vecs(&mut r, &v);
         mirror_vecs(&mut q, &v);
+        reflections += 1;
     }
 
-    Ok((q.transpose(), r))
+    // the reflections leave rounding noise where the zeros should be
+    for i in 1..width {
+        for j in 0..i {
+            r.set(i, j, 0.0.into());
+        }
+    }
+
+    Ok((q.transpose(), r, reflections))
+}
+
+impl Matrix<f32> {
+    /// `det(A) = det(Q)·det(R)`: `det(R)` is the product of its diagonal and
+    /// every Householder reflection flips the sign of `det(Q)`. Independent of
+    /// the LU path, so the two can be checked against each other.
+    #[allow(dead_code)]
+    pub fn determinant_via_qr(&self) -> Result<f32, MatrixError> {
+        let (_, r, reflections) = householder(self)?;
+        let det: f32 = (0..r.width()).map(|i| r.get(i, i)).product();
+        Ok(if reflections % 2 == 0 { det } else { -det })
+    }
 }
 
 fn mirror_vecs<T>(vecs: &mut Matrix<T>, mirror_direction: &Matrix<T>)
@@ -96,6 +144,10 @@ fn zero_by_rotation(
     let a = r.get(element_to_zero_column, element_to_zero_column);
     let b = r.get(element_to_zero_row, element_to_zero_column);
     let ab = (a * a + b * b).sqrt();
+    if ab == 0.0 {
+        // cos = 1, sin = 0: both entries are zero already and 0/0 would be NaN
+        return;
+    }
     let cos = a / ab;
     let sin = -b / ab;
 
@@ -112,34 +164,59 @@ fn zero_by_rotation(
     }
 }
 
+/// QR decomposition by Gram-Schmidt with reorthogonalization. A tall `m x n`
+/// input gives the thin factorization: `Q` is `m x n` with orthonormal
+/// columns and `R` is `n x n`.
 pub fn qr_gram_schmidt<T>(
     mat: &Matrix<T>,
     reortho_epsilon: f32,
 ) -> Result<(Matrix<T>, Matrix<T>), MatrixError>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    let (q, r, _) = qr_gram_schmidt_masked(mat, reortho_epsilon, DEPENDENT_COLUMN_EPSILON)?;
+    Ok((q, r))
+}
+
+/// `Q`, `R` and the mask of linearly dependent columns.
+pub type MaskedQr<T> = (Matrix<T>, Matrix<T>, Vec<bool>);
+
+/// Gram-Schmidt QR that survives rank deficiency. A column whose norm after
+/// orthogonalization drops below `dependence_epsilon` times its original norm
+/// is marked `true` in the returned mask and gets a zero column in `Q` (and a
+/// zero on the diagonal of `R`) instead of being normalized.
+pub fn qr_gram_schmidt_masked<T>(
+    mat: &Matrix<T>,
+    reortho_epsilon: f32,
+    dependence_epsilon: f32,
+) -> Result<MaskedQr<T>, MatrixError>
 where
     T: NumNonRef,
     for<'a> &'a T: NumRef<T>,
 {
     let width = mat.width();
-    if mat.width() != mat.height() {
-        return Err(MatrixError::NotSquare);
+    let height = mat.height();
+    if height < width {
+        return Err(MatrixError::SizeMismatch);
     }
 
-    let mut q = Matrix::new(width, width);
+    let mut q = Matrix::new(width, height);
     let mut r = Matrix::new(width, width);
+    let mut dependent = Vec::with_capacity(width);
 
     for j in 0..width {
         let mut p = mat.column(j);
 
-        loop {
+        for pass in 0.. {
             let mut delta = 0.0;
             for i in 0..j {
                 let mut dot = 0.0.into();
-                for k in 0..width {
+                for k in 0..height {
                     dot = dot + &q.get(k, i).conjugate() * p.get(k, 0);
                 }
 
-                for k in 0..width {
+                for k in 0..height {
                     let a = p.get(k, 0) - q.get(k, i) * &dot;
                     delta += (&a - p.get(k, 0)).norm_squared();
                     p.set(k, 0, a);
@@ -148,22 +225,33 @@ where
             if delta < reortho_epsilon {
                 break;
             }
+            if pass + 1 == MAX_REORTHOGONALIZATIONS {
+                return Err(MatrixError::DidNotConverge {
+                    iterations: MAX_REORTHOGONALIZATIONS,
+                    residual: delta,
+                });
+            }
         }
 
-        for i in 0..width {
-            q.set(i, j, p.get(i, 0) / &p.norm().into());
+        let p_norm = p.col_norm(0);
+        let is_dependent = p_norm <= dependence_epsilon * mat.col_norm(j);
+        dependent.push(is_dependent);
+        if !is_dependent {
+            for i in 0..height {
+                q.set(i, j, p.get(i, 0) / &p_norm.into());
+            }
         }
 
         for i in 0..j + 1 {
             let mut dot = 0.0.into();
-            for k in 0..width {
+            for k in 0..height {
                 dot = dot + &q.get(k, i).conjugate() * mat.get(k, j);
             }
             r.set(i, j, dot);
         }
     }
 
-    Ok((q, r))
+    Ok((q, r, dependent))
 }
 
 pub fn gauss_from_qr<T>(
@@ -177,101 +265,124 @@ where
 {
     if q.width() != q.height()
         || r.width() != r.height()
-        || b.width() != 1
         || b.height() != q.height()
         || r.width() != q.width()
     {
         return Err(MatrixError::SizeMismatch);
     }
-    let v = (q.hermetian_transpose() * b)?;
-    Ok(r_gauss(r, &v))
+    let qh = q.hermetian_transpose();
+    let v = if b.width() == 1 {
+        qh.mul_vec(b)?
+    } else {
+        (qh * b)?
+    };
+    r.solve_upper_triangular(&v)
 }
 
-fn r_gauss<T>(r: &Matrix<T>, b: &Matrix<T>) -> Matrix<T>
+/// The factors from a QR decomposition together with how long it took,
+/// `∥QR - A∥` and `∥QᴴQ - I∥`.
+pub struct QrReport<T>
 where
     T: NumNonRef,
     for<'a> &'a T: NumRef<T>,
 {
-    let mut x = Matrix::new(1, r.width());
-    for i in 0..r.width() {
-        let mut xi = b.get(r.width() - i - 1, 0).clone();
-        for j in 0..i {
-            xi = xi - r.get(r.width() - i - 1, r.width() - j - 1) * x.get(r.width() - j - 1, 0);
-        }
-        x.set(
-            r.width() - i - 1,
-            0,
-            &xi / r.get(r.width() - i - 1, r.width() - i - 1),
-        );
-    }
-    x
+    pub q: Matrix<T>,
+    pub r: Matrix<T>,
+    pub duration: Duration,
+    pub reconstruction_error: f32,
+    pub orthogonality_error: f32,
+}
+
+/// Runs `decompose` on `mat` and measures the result.
+pub fn qr_report<T, F>(mat: &Matrix<T>, decompose: F) -> Result<QrReport<T>, MatrixError>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+    F: Fn(&Matrix<T>) -> Result<(Matrix<T>, Matrix<T>), MatrixError>,
+{
+    let ((q, r), duration) = measure!(decompose(mat)?);
+    let reconstruction_error = ((&q * &r)? - mat)?.norm();
+    let orthogonality_error = q.orthogonality_error();
+
+    Ok(QrReport {
+        q,
+        r,
+        duration,
+        reconstruction_error,
+        orthogonality_error,
+    })
 }
 
 pub fn make_qr(dir: &PathBuf, problem: usize) -> Result<(), MatrixError> {
-    let (mat, method) = read_mat(&mut File::open(dir.join(format!("Amat{problem}.m")))?)?;
+    let (mat, header) = read_mat(&mut File::open(dir.join(format!("Amat{problem}.m")))?)?;
     println!("Problem {}", problem);
 
-    match method {
+    match header.method {
         Some(method) => match method {
             QRMethod::Householder => match mat {
                 Either::Left(mat) => {
-                    let ((q, r), duration) = measure!(qr_householder(&mat)?);
-                    write_mat_f32(&q, &dir.join(format!("Qmat{problem}.m")))?;
-                    write_mat_f32(&r, &dir.join(format!("Rmat{problem}.m")))?;
+                    let report = qr_report(&mat, qr_householder)?;
+                    write_mat_f32(&report.q, &dir.join(format!("Qmat{problem}.m")))?;
+                    write_mat_f32(&report.r, &dir.join(format!("Rmat{problem}.m")))?;
 
                     println!(
-                        "\tTook {}μs, ∥QR - A∥ = {}",
-                        duration.as_micros(),
-                        ((q * r)? - mat)?.norm()
+                        "\tTook {}μs, ∥QR - A∥ = {}, ∥QᴴQ - I∥ = {}",
+                        report.duration.as_micros(),
+                        report.reconstruction_error,
+                        report.orthogonality_error
                     );
                 }
                 Either::Right(mat) => {
-                    let ((q, r), duration) = measure!(qr_householder(&mat)?);
-                    write_mat_complex(&q, &dir.join(format!("Qmat{problem}.m")))?;
-                    write_mat_complex(&r, &dir.join(format!("Rmat{problem}.m")))?;
+                    let report = qr_report(&mat, qr_householder)?;
+                    write_mat_complex(&report.q, &dir.join(format!("Qmat{problem}.m")))?;
+                    write_mat_complex(&report.r, &dir.join(format!("Rmat{problem}.m")))?;
 
                     println!(
-                        "\tTook {}μs, ∥QR - A∥ = {}",
-                        duration.as_micros(),
-                        ((q * r)? - mat)?.norm()
+                        "\tTook {}μs, ∥QR - A∥ = {}, ∥QᴴQ - I∥ = {}",
+                        report.duration.as_micros(),
+                        report.reconstruction_error,
+                        report.orthogonality_error
                     );
                 }
             },
             QRMethod::Givens => match mat {
                 Either::Left(mat) => {
-                    let ((q, r), duration) = measure!(qr_givens(&mat)?);
-                    write_mat_f32(&q, &dir.join(format!("Qmat{problem}.m")))?;
-                    write_mat_f32(&r, &dir.join(format!("Rmat{problem}.m")))?;
+                    let report = qr_report(&mat, qr_givens)?;
+                    write_mat_f32(&report.q, &dir.join(format!("Qmat{problem}.m")))?;
+                    write_mat_f32(&report.r, &dir.join(format!("Rmat{problem}.m")))?;
 
                     println!(
-                        "\tTook {}μs, ∥QR - A∥ = {}",
-                        duration.as_micros(),
-                        ((q * r)? - mat)?.norm()
+                        "\tTook {}μs, ∥QR - A∥ = {}, ∥QᴴQ - I∥ = {}",
+                        report.duration.as_micros(),
+                        report.reconstruction_error,
+                        report.orthogonality_error
                     );
                 }
                 Either::Right(_) => return Err(MatrixError::UnsopportedOperation),
             },
             QRMethod::GramSchmidt => match mat {
                 Either::Left(mat) => {
-                    let ((q, r), duration) = measure!(qr_gram_schmidt(&mat, 0.1)?);
-                    write_mat_f32(&q, &dir.join(format!("Qmat{problem}.m")))?;
-                    write_mat_f32(&r, &dir.join(format!("Rmat{problem}.m")))?;
+                    let report = qr_report(&mat, |m| qr_gram_schmidt(m, header.epsilon))?;
+                    write_mat_f32(&report.q, &dir.join(format!("Qmat{problem}.m")))?;
+                    write_mat_f32(&report.r, &dir.join(format!("Rmat{problem}.m")))?;
 
                     println!(
-                        "\tTook {}μs, ∥QR - A∥ = {}",
-                        duration.as_micros(),
-                        ((q * r)? - mat)?.norm()
+                        "\tTook {}μs, ∥QR - A∥ = {}, ∥QᴴQ - I∥ = {}",
+                        report.duration.as_micros(),
+                        report.reconstruction_error,
+                        report.orthogonality_error
                     );
                 }
                 Either::Right(mat) => {
-                    let ((q, r), duration) = measure!(qr_gram_schmidt(&mat, 0.1)?);
-                    write_mat_complex(&q, &dir.join(format!("Qmat{problem}.m")))?;
-                    write_mat_complex(&r, &dir.join(format!("Rmat{problem}.m")))?;
+                    let report = qr_report(&mat, |m| qr_gram_schmidt(m, header.epsilon))?;
+                    write_mat_complex(&report.q, &dir.join(format!("Qmat{problem}.m")))?;
+                    write_mat_complex(&report.r, &dir.join(format!("Rmat{problem}.m")))?;
 
                     println!(
-                        "\tTook {}μs, ∥QR - A∥ = {}",
-                        duration.as_micros(),
-                        ((q * r)? - mat)?.norm()
+                        "\tTook {}μs, ∥QR - A∥ = {}, ∥QᴴQ - I∥ = {}",
+                        report.duration.as_micros(),
+                        report.reconstruction_error,
+                        report.orthogonality_error
                     );
                 }
             },
@@ -280,25 +391,27 @@ pub fn make_qr(dir: &PathBuf, problem: usize) -> Result<(), MatrixError> {
             println!("No method given! Assuming Gram-Shmidt");
             match mat {
                 Either::Left(mat) => {
-                    let ((q, r), duration) = measure!(qr_gram_schmidt(&mat, 0.1)?);
-                    write_mat_f32(&q, &dir.join(format!("Qmat{problem}.m")))?;
-                    write_mat_f32(&r, &dir.join(format!("Rmat{problem}.m")))?;
+                    let report = qr_report(&mat, |m| qr_gram_schmidt(m, header.epsilon))?;
+                    write_mat_f32(&report.q, &dir.join(format!("Qmat{problem}.m")))?;
+                    write_mat_f32(&report.r, &dir.join(format!("Rmat{problem}.m")))?;
 
                     println!(
-                        "\tTook {}μs, ∥QR - A∥ = {}",
-                        duration.as_micros(),
-                        ((q * r)? - mat)?.norm()
+                        "\tTook {}μs, ∥QR - A∥ = {}, ∥QᴴQ - I∥ = {}",
+                        report.duration.as_micros(),
+                        report.reconstruction_error,
+                        report.orthogonality_error
                     );
                 }
                 Either::Right(mat) => {
-                    let ((q, r), duration) = measure!(qr_gram_schmidt(&mat, 0.1)?);
-                    write_mat_complex(&q, &dir.join(format!("Qmat{problem}.m")))?;
-                    write_mat_complex(&r, &dir.join(format!("Rmat{problem}.m")))?;
+                    let report = qr_report(&mat, |m| qr_gram_schmidt(m, header.epsilon))?;
+                    write_mat_complex(&report.q, &dir.join(format!("Qmat{problem}.m")))?;
+                    write_mat_complex(&report.r, &dir.join(format!("Rmat{problem}.m")))?;
 
                     println!(
-                        "\tTook {}μs, ∥QR - A∥ = {}",
-                        duration.as_micros(),
-                        ((q * r)? - mat)?.norm()
+                        "\tTook {}μs, ∥QR - A∥ = {}, ∥QᴴQ - I∥ = {}",
+                        report.duration.as_micros(),
+                        report.reconstruction_error,
+                        report.orthogonality_error
                     );
                 }
             }
@@ -308,71 +421,136 @@ pub fn make_qr(dir: &PathBuf, problem: usize) -> Result<(), MatrixError> {
     Ok(())
 }
 
-pub fn qr_gauss(dir: &PathBuf, problem: usize) -> Result<(), MatrixError> {
-    let (b, _) = read_mat(&mut File::open(dir.join(format!("bvec{problem}.m")))?)?;
+pub fn bench_qr(dir: &Path, problem: usize) -> Result<(), MatrixError> {
+    let (mat, header) = read_mat(&mut File::open(dir.join(format!("Amat{problem}.m")))?)?;
 
     println!("Problem {}", problem);
+    println!(
+        "\t{:<14}{:>12}{:>16}{:>16}",
+        "method", "time, μs", "∥QR - A∥", "∥QᴴQ - I∥"
+    );
+
+    match mat {
+        Either::Left(mat) => {
+            bench_qr_method("Householder", &mat, qr_householder)?;
+            bench_qr_method("Givens", &mat, qr_givens)?;
+            bench_qr_method("Gram-Schmidt", &mat, |m| qr_gram_schmidt(m, header.epsilon))?;
+        }
+        Either::Right(mat) => {
+            bench_qr_method("Householder", &mat, qr_householder)?;
+            println!("\t{:<14}{:>12}", "Givens", "n/a");
+            bench_qr_method("Gram-Schmidt", &mat, |m| qr_gram_schmidt(m, header.epsilon))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn bench_qr_method<T, F>(name: &str, mat: &Matrix<T>, method: F) -> Result<(), MatrixError>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+    F: Fn(&Matrix<T>) -> Result<(Matrix<T>, Matrix<T>), MatrixError>,
+{
+    let report = qr_report(mat, method)?;
 
-    let (q, r) = match (
-        File::open(dir.join(format!("Qmat{problem}.m"))),
-        File::open(dir.join(format!("Rmat{problem}.m"))),
-    ) {
-        (Ok(mut q), Ok(mut r)) => (read_mat(&mut q)?.0, read_mat(&mut r)?.0),
+    println!(
+        "\t{:<14}{:>12}{:>16e}{:>16e}",
+        name,
+        report.duration.as_micros(),
+        report.reconstruction_error,
+        report.orthogonality_error
+    );
+
+    Ok(())
+}
+
+/// Solves `Ax = b` for `bvec{problem}.m`, reusing `Qmat`/`Rmat` if they exist
+/// and factoring `Amat` otherwise, and writes `xvec{problem}.m`. The residual
+/// is `∥QRx - b∥`.
+pub fn solve_qr(dir: &Path, problem: usize) -> Result<SolveReport, MatrixError> {
+    let b_name = format!("bvec{problem}.m");
+    let q_name = format!("Qmat{problem}.m");
+    let r_name = format!("Rmat{problem}.m");
+    let a_name = format!("Amat{problem}.m");
+
+    let (b, _) = read_mat(&mut File::open(dir.join(&b_name))?)?;
+
+    let (q, r, qr_name) = match (File::open(dir.join(&q_name)), File::open(dir.join(&r_name))) {
+        (Ok(mut q), Ok(mut r)) => (read_mat(&mut q)?.0, read_mat(&mut r)?.0, q_name.clone()),
         _ => {
-            let (mat, method) = read_mat(&mut File::open(dir.join(format!("Amat{problem}.m")))?)?;
-            match mat {
+            let (mat, header) = read_mat(&mut File::open(dir.join(&a_name))?)?;
+            let (q, r) = match mat {
                 Either::Left(mat) => {
-                    let (q, r) = match method {
+                    let (q, r) = match header.method {
                         Some(m) => match m {
                             QRMethod::Householder => qr_householder(&mat)?,
                             QRMethod::Givens => qr_givens(&mat)?,
-                            QRMethod::GramSchmidt => qr_gram_schmidt(&mat, 0.1)?,
+                            QRMethod::GramSchmidt => qr_gram_schmidt(&mat, header.epsilon)?,
                         },
                         None => qr_householder(&mat)?,
                     };
                     (Either::Left(q), Either::Left(r))
                 }
                 Either::Right(mat) => {
-                    let (q, r) = match method {
+                    let (q, r) = match header.method {
                         Some(m) => match m {
                             QRMethod::Householder => qr_householder(&mat)?,
-                            QRMethod::GramSchmidt => qr_gram_schmidt(&mat, 0.1)?,
+                            QRMethod::GramSchmidt => qr_gram_schmidt(&mat, header.epsilon)?,
                             _ => panic!("No Givens method for complex matrices"),
                         },
                         None => qr_householder(&mat)?,
                     };
                     (Either::Right(q), Either::Right(r))
                 }
-            }
+            };
+            (q, r, a_name)
         }
     };
 
-    match b {
-        Either::Left(b) => {
-            let q = q.unwrap_left();
-            let r = r.unwrap_left();
-            let (x, duration) = measure!(gauss_from_qr(q, r, &b)?);
-            write_mat_f32(&x, &dir.join(format!("xvec{problem}.m")))?;
-
-            println!(
-                "\tTook {}μs, ∥QRx - b∥ = {}",
-                duration.as_micros(),
-                ((q * (r * x)?)? - b)?.norm()
-            );
+    let x_path = dir.join(format!("xvec{problem}.m"));
+    let (residual, duration) = match (q, r, b) {
+        (Either::Left(q), Either::Left(r), Either::Left(b)) => {
+            let (x, duration) = measure!(gauss_from_qr(&q, &r, &b)?);
+            write_mat_f32(&x, &x_path)?;
+            ((q.mul_vec(&r.mul_vec(&x)?)? - b)?.norm(), duration)
         }
-        Either::Right(b) => {
-            let q = q.unwrap_right();
-            let r = r.unwrap_right();
-            let (x, duration) = measure!(gauss_from_qr(q, r, &b)?);
-            write_mat_complex(&x, &dir.join(format!("xvec{problem}.m")))?;
-
-            println!(
-                "\tTook {}μs, ∥QRx - b∥ = {}",
-                duration.as_micros(),
-                ((q * (r * x)?)? - b)?.norm()
-            );
+        (Either::Right(q), Either::Right(r), Either::Right(b)) => {
+            let (x, duration) = measure!(gauss_from_qr(&q, &r, &b)?);
+            write_mat_complex(&x, &x_path)?;
+            ((q.mul_vec(&r.mul_vec(&x)?)? - b)?.norm(), duration)
         }
-    }
+        (q, r, _) if q.is_left() != r.is_left() => {
+            return Err(MatrixError::real_complex_mismatch(
+                q.is_left(),
+                q_name,
+                r_name,
+            ));
+        }
+        (q, _, _) => {
+            return Err(MatrixError::real_complex_mismatch(
+                q.is_left(),
+                qr_name,
+                b_name,
+            ))
+        }
+    };
+
+    Ok(SolveReport {
+        x_path,
+        residual,
+        duration,
+    })
+}
+
+pub fn qr_gauss(dir: &PathBuf, problem: usize) -> Result<(), MatrixError> {
+    let report = solve_qr(dir, problem)?;
+    println!("Problem {}", problem);
+    println!(
+        "\tTook {}μs, ∥QRx - b∥ = {}",
+        report.duration.as_micros(),
+        report.residual
+    );
 
     Ok(())
 }
@@ -0,0 +1,77 @@
+use crate::{
+    matrix::{Matrix, MatrixError},
+    number::{NumNonRef, NumRef},
+};
+
+/// Sparse matrix in triplet (COO) form. Repeated positions are summed.
+#[derive(Clone, Debug)]
+pub struct SparseMatrix<T>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    entries: Vec<(usize, usize, T)>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> SparseMatrix<T>
+where
+    T: NumNonRef,
+    for<'a> &'a T: NumRef<T>,
+{
+    #[allow(dead_code)]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            width,
+            height,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn push(&mut self, row: usize, column: usize, val: T) {
+        assert!(row < self.height && column < self.width);
+        self.entries.push((row, column, val));
+    }
+
+    #[inline(always)]
+    #[allow(dead_code)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    #[inline(always)]
+    #[allow(dead_code)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    #[inline(always)]
+    #[allow(dead_code)]
+    pub fn entries(&self) -> &[(usize, usize, T)] {
+        &self.entries
+    }
+
+    #[allow(dead_code)]
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let mut res = Matrix::new(self.width, self.height);
+        for (row, column, val) in &self.entries {
+            let sum = res.get(*row, *column) + val;
+            res.set(*row, *column, sum);
+        }
+        res
+    }
+
+    #[allow(dead_code)]
+    pub fn mul_vec(&self, v: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        if v.width() != 1 || self.width != v.height() {
+            return Err(MatrixError::SizeMismatch);
+        }
+
+        let mut res = Matrix::new(1, self.height);
+        for (row, column, val) in &self.entries {
+            let sum = res.get(*row, 0) + val * v.get(*column, 0);
+            res.set(*row, 0, sum);
+        }
+        Ok(res)
+    }
+}
@@ -0,0 +1,108 @@
+use crate::matrix::{Matrix, MatrixError};
+
+const MAX_SWEEPS: usize = 60;
+const ORTHOGONALITY_EPSILON: f32 = 1e-6;
+
+/// `(U, singular values, Vᵀ)`
+pub type Svd = (Matrix<f32>, Vec<f32>, Matrix<f32>);
+
+/// Thin singular value decomposition `A = U Σ Vᵀ` by one-sided Jacobi rotations.
+///
+/// For an `m x n` matrix with `k = min(m, n)` this returns `U` as `m x k`, the
+/// `k` singular values in decreasing order and `Vᵀ` as `k x n`. Columns of `U`
+/// belonging to zero singular values are left as zero.
+#[allow(dead_code)]
+pub fn svd(mat: &Matrix<f32>) -> Result<Svd, MatrixError> {
+    if mat.height() < mat.width() {
+        let (u, sigma, vt) = svd(&mat.transpose())?;
+        return Ok((vt.transpose(), sigma, u.transpose()));
+    }
+
+    let n = mat.width();
+    let mut u = mat.clone();
+    let mut v = Matrix::identity(n);
+
+    for _ in 0..MAX_SWEEPS {
+        let mut rotated = false;
+        for p in 0..n {
+            for q in p + 1..n {
+                let alpha = column_dot(&u, p, p);
+                let beta = column_dot(&u, q, q);
+                let gamma = column_dot(&u, p, q);
+                if gamma.abs() <= ORTHOGONALITY_EPSILON * (alpha * beta).sqrt() {
+                    continue;
+                }
+                rotated = true;
+
+                let zeta = (beta - alpha) / (2.0 * gamma);
+                let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                let cos = 1.0 / (1.0 + t * t).sqrt();
+                let sin = cos * t;
+                rotate_columns(&mut u, p, q, cos, sin);
+                rotate_columns(&mut v, p, q, cos, sin);
+            }
+        }
+        if !rotated {
+            break;
+        }
+    }
+
+    let mut order: Vec<_> = (0..n).map(|j| (column_dot(&u, j, j).sqrt(), j)).collect();
+    order.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut u_sorted = Matrix::new(n, u.height());
+    let mut vt = Matrix::new(n, n);
+    let mut sigma = Vec::with_capacity(n);
+    for (k, &(s, j)) in order.iter().enumerate() {
+        for i in 0..u.height() {
+            let x = if s > 0.0 { u.get(i, j) / s } else { 0.0 };
+            u_sorted.set(i, k, x);
+        }
+        for i in 0..n {
+            vt.set(k, i, *v.get(i, j));
+        }
+        sigma.push(s);
+    }
+
+    Ok((u_sorted, sigma, vt))
+}
+
+fn column_dot(mat: &Matrix<f32>, a: usize, b: usize) -> f32 {
+    let mut sum = 0.0;
+    for i in 0..mat.height() {
+        sum += mat.get(i, a) * mat.get(i, b);
+    }
+    sum
+}
+
+fn rotate_columns(mat: &mut Matrix<f32>, p: usize, q: usize, cos: f32, sin: f32) {
+    for i in 0..mat.height() {
+        let a = *mat.get(i, p);
+        let b = *mat.get(i, q);
+        mat.set(i, p, cos * a - sin * b);
+        mat.set(i, q, sin * a + cos * b);
+    }
+}
+
+impl Matrix<f32> {
+    /// Moore-Penrose pseudoinverse `V Σ⁺ Uᵀ`, where singular values below
+    /// `epsilon` are treated as zero.
+    #[allow(dead_code)]
+    pub fn pseudoinverse(&self, epsilon: f32) -> Matrix<f32> {
+        let (u, sigma, vt) = svd(self).expect("svd() of a real matrix cannot fail");
+
+        let mut res = Matrix::new(self.height(), self.width());
+        for (k, s) in sigma.iter().enumerate() {
+            if *s <= epsilon {
+                continue;
+            }
+            for i in 0..self.width() {
+                for j in 0..self.height() {
+                    let x = res.get(i, j) + vt.get(k, i) * u.get(j, k) / s;
+                    res.set(i, j, x);
+                }
+            }
+        }
+        res
+    }
+}
@@ -1,11 +1,2477 @@
-use std::{fs::File, io::Write, path::PathBuf, time::Instant};
+use std::{ops::Deref, path::PathBuf};
 
 use crate::{
-    eigen::characteristic_polynomial,
-    io::{read_mat, write_mat_complex, write_mat_f32, Either, QRMethod},
-    longint::LongInt,
-    matrix::MatrixError,
-    measure,
-    number::from_f32_mat,
-    qr::{gauss_from_qr, qr_givens, qr_gram_schmidt, qr_householder},
+    banded::BandedMatrix,
+    complex::Complex,
+    eigen::{
+        characteristic_polynomial, characteristic_polynomial_max_digits,
+        characteristic_polynomial_with_tol, eigenvalues, eigenvectors, find_poly,
+        inverse_power_iteration, is_tridiagonal,
+    },
+    fraction::Fraction,
+    io::{
+        read_mat, read_mat_padded, read_mats, write_eigen, write_mat_complex, write_mat_f32,
+        Either, QRMethod, DEFAULT_GRAM_SCHMIDT_EPSILON,
+    },
+    iterative::{conjugate_gradient, gauss_seidel, jacobi, jacobi_preconditioner, pcg},
+    longint::{LongInt, ParseLongIntError},
+    lu::{
+        gauss_eliminate, lu_decomposition, lu_decomposition_with_options, lu_gauss,
+        lu_reconstruction_error, lu_report, make_lu, par_lu_decomposition, permutation_from_matrix,
+        permutation_matrix, shifted_solve, solve_lu, solve_tridiagonal, solve_with_refinement,
+        LuFactorization,
+    },
+    matrix::{Matrix, MatrixError},
+    number::{from_f32_mat, NumNonRef},
+    parse_args,
+    poly::{interpolate, Polynome},
+    qr::{
+        gauss_from_qr, qr_givens, qr_gram_schmidt, qr_gram_schmidt_masked, qr_householder,
+        qr_report, solve_qr,
+    },
+    sparse::SparseMatrix,
+    svd::svd,
+    ArgsError, Operation,
 };
+
+/// A scratch directory under the system temp dir, removed again on drop so a
+/// failing assertion does not leave fixtures behind.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("task1_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = PathBuf;
+
+    fn deref(&self) -> &PathBuf {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn lu_factorization_solves_many_rhs() {
+    let a = Matrix::from_vec(vec![3.0, 1.0, 1.0, 1.0, 2.0, -1.0, 0.0, 1.0, 2.0], 3).unwrap();
+    let lu = LuFactorization::new(&a).unwrap();
+
+    for b in [
+        vec![1.0, 0.0, 0.0],
+        vec![5.0, 2.0, 3.0],
+        vec![-1.0, 4.0, 0.5],
+    ] {
+        let b = Matrix::from_vec(b, 1).unwrap();
+        let x = lu.solve(&b).unwrap();
+        assert!(((&a * x).unwrap() - b).unwrap().norm() < 1e-5);
+    }
+}
+
+#[test]
+fn mul_vec_matches_generic_mul() {
+    let a = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 3).unwrap();
+    let v = Matrix::from_vec(vec![1.0, -1.0, 0.5], 1).unwrap();
+
+    let fast = a.mul_vec(&v).unwrap();
+    let slow = (&a * &v).unwrap();
+    assert_eq!(fast.elems_raw(), slow.elems_raw());
+    assert_eq!((fast.width(), fast.height()), (1, 2));
+
+    assert!(a.mul_vec(&a).is_err());
+}
+
+#[test]
+fn blocked_mul_matches_naive() {
+    let n = 256;
+    let a = Matrix::from_vec((0..n * n).map(|i| (i % 7) as f32 - 3.0).collect(), n).unwrap();
+    let b = Matrix::from_vec((0..n * n).map(|i| (i % 5) as f32 * 0.5).collect(), n).unwrap();
+
+    let naive = |a: &Matrix<f32>, b: &Matrix<f32>| {
+        let mut c = Matrix::new(n, n);
+        for i in 0..n {
+            for j in 0..n {
+                let mut sum = 0.0;
+                for k in 0..n {
+                    sum += a.get(i, k) * b.get(k, j);
+                }
+                c.set(i, j, sum);
+            }
+        }
+        c
+    };
+
+    let expected = naive(&a, &b);
+    let c = (&a * &b).unwrap();
+    assert_eq!(c.elems_raw(), expected.elems_raw());
+}
+
+#[test]
+fn lu_longint_reconstructs_exactly() {
+    // the largest pivot always comes first here, so every multiplier is an integer
+    let a = Matrix::from_vec(vec![2.0, 1.0, -1.0, -2.0, 2.0, 2.0, 2.0, 4.0, 2.0], 3).unwrap();
+    let a = from_f32_mat::<LongInt>(&a);
+
+    let (perm, l, u) = lu_decomposition(&a).unwrap();
+    assert_eq!(perm, vec![0, 1, 2]);
+    assert_eq!(format!("{}", l), "| 1 0 0 |\n| -1 1 0 |\n| 1 1 1 |\n");
+    assert_eq!(format!("{}", u), "| 2 1 -1 |\n| 0 3 1 |\n| 0 0 2 |\n");
+    assert_eq!(format!("{}", (l * u).unwrap()), format!("{}", a));
+
+    // with pivoting the multipliers of this one are fractions
+    let a = Matrix::from_vec(vec![2.0, 1.0, -1.0, -4.0, -1.0, 3.0, 8.0, 7.0, 1.0], 3).unwrap();
+    let a = from_f32_mat::<Fraction<LongInt>>(&a);
+    let (perm, l, u) = lu_decomposition(&a).unwrap();
+    assert_eq!(perm[0], 2);
+    assert_eq!(lu_reconstruction_error(&a, &perm, &l, &u).unwrap(), 0.0);
+}
+
+#[test]
+fn longint_from_64_bit() {
+    assert_eq!(LongInt::from(i64::MAX).to_decimal(), "9223372036854775807");
+    assert_eq!(LongInt::from(i64::MIN).to_decimal(), "-9223372036854775808");
+    assert_eq!(LongInt::from(-5_000_000_000i64).to_decimal(), "-5000000000");
+    assert_eq!(LongInt::from(u64::MAX).to_decimal(), "18446744073709551615");
+    assert_eq!(LongInt::from(0u64).to_decimal(), "0");
+    assert_eq!(LongInt::from(3e10f32).to_decimal(), "30000001024");
+}
+
+#[test]
+fn longint_eq_ignores_trailing_zeros_and_zero_sign() {
+    let mut padded = LongInt::new();
+    padded.set(0, 5);
+    padded.set(1, 0);
+    assert_eq!(padded, LongInt::from(5));
+
+    let negative_zero = -LongInt::from(0);
+    assert_eq!(negative_zero, LongInt::from(0));
+    assert_eq!(negative_zero.to_decimal(), "0");
+    assert!(negative_zero >= LongInt::from(0));
+
+    assert_ne!(LongInt::from(-5), LongInt::from(5));
+    assert!(LongInt::from(-5) < LongInt::from(-3));
+}
+
+#[test]
+fn longint_borrow_and_negative_divisor() {
+    // a borrow used to take one too many from the digit it was subtracted from
+    assert_eq!(LongInt::from(256) - LongInt::from(1), LongInt::from(255));
+    assert_eq!(
+        LongInt::from(65536) - LongInt::from(1),
+        LongInt::from(65535)
+    );
+    assert_eq!(LongInt::from(1000) - LongInt::from(1), LongInt::from(999));
+    assert_eq!(
+        LongInt::from(1) - LongInt::from(65536),
+        LongInt::from(-65535)
+    );
+
+    // a negative divisor compared against the positive running remainder
+    assert_eq!(LongInt::from(7) / LongInt::from(-2), LongInt::from(-3));
+    assert_eq!(LongInt::from(7) % LongInt::from(-2), LongInt::from(1));
+    assert_eq!(
+        LongInt::from(70000) / LongInt::from(-3),
+        LongInt::from(-23333)
+    );
+    assert_eq!(LongInt::from(70000) % LongInt::from(-3), LongInt::from(1));
+}
+
+#[test]
+fn longint_format_radix() {
+    assert_eq!(format!("{:x}", LongInt::from(255)), "ff");
+    assert_eq!(format!("{:X}", LongInt::from(255)), "FF");
+    assert_eq!(format!("{:#x}", LongInt::from(-255)), "-0xff");
+    assert_eq!(LongInt::from(10).format_radix(2), "1010");
+    assert_eq!(LongInt::from(-35).format_radix(36), "-z");
+    assert_eq!(LongInt::from(0).format_radix(16), "0");
+}
+
+#[test]
+fn json_round_trip() {
+    let a = Matrix::from_vec(vec![1.0, -2.5, 3.25e-3, 4e10, 0.0, 6.0], 3).unwrap();
+    let json = a.to_json();
+    assert_eq!(
+        json,
+        "{\"width\":3,\"height\":2,\"data\":[1,-2.5,0.00325,40000000000,0,6]}"
+    );
+    let b = Matrix::<f32>::from_json(&json).unwrap();
+    assert_eq!((b.width(), b.height()), (3, 2));
+    assert_eq!(b.elems_raw(), a.elems_raw());
+
+    let z = Matrix::from_vec(vec![Complex::new(1.0, -1.0), Complex::new(0.5, 2.0)], 1).unwrap();
+    let json = z.to_json();
+    assert_eq!(json, "{\"width\":1,\"height\":2,\"data\":[[1,-1],[0.5,2]]}");
+    let w = Matrix::<Complex>::from_json(&json).unwrap();
+    assert_eq!((w.width(), w.height()), (1, 2));
+    assert_eq!(w.elems_raw(), z.elems_raw());
+
+    let spaced = "{ \"data\": [1, 2], \"height\": 1, \"width\": 2 }";
+    assert_eq!(
+        Matrix::<f32>::from_json(spaced).unwrap().elems_raw(),
+        &[1.0, 2.0]
+    );
+    assert!(Matrix::<f32>::from_json("{\"width\":2,\"height\":2,\"data\":[1]}").is_err());
+    assert!(Matrix::<f32>::from_json("{\"width\":1,\"height\":1,\"data\":[[1,2]]}").is_err());
+    // width * height does not fit in a usize
+    assert!(matches!(
+        Matrix::<f32>::from_json("{\"width\":10000000000000000000,\"height\":2,\"data\":[]}"),
+        Err(MatrixError::InvalidFileFormat)
+    ));
+}
+
+#[test]
+fn json_round_trips_non_finite_values() {
+    let a = Matrix::from_vec(vec![f32::NAN, f32::INFINITY, f32::NEG_INFINITY, 1.0], 2).unwrap();
+    let json = a.to_json();
+    assert_eq!(
+        json,
+        "{\"width\":2,\"height\":2,\"data\":[\"NaN\",\"Infinity\",\"-Infinity\",1]}"
+    );
+    let b = Matrix::<f32>::from_json(&json).unwrap();
+    assert!(b.get(0, 0).is_nan());
+    assert_eq!(&b.elems_raw()[1..], &a.elems_raw()[1..]);
+
+    let z = Matrix::from_vec(vec![Complex::new(f32::INFINITY, f32::NAN)], 1).unwrap();
+    let w = Matrix::<Complex>::from_json(&z.to_json()).unwrap();
+    assert_eq!(w.get(0, 0).re, f32::INFINITY);
+    assert!(w.get(0, 0).im.is_nan());
+
+    assert!(Matrix::<f32>::from_json("{\"width\":1,\"height\":1,\"data\":[\"inf\"]}").is_err());
+}
+
+#[test]
+fn thomas_matches_lu() {
+    let a = Matrix::from_vec(
+        vec![
+            4.0, 2.0, 0.0, 0.0, 0.0, //
+            1.0, 3.0, 4.0, 0.0, 0.0, //
+            0.0, 2.0, 5.0, 2.0, 0.0, //
+            0.0, 0.0, 5.0, 5.0, 2.0, //
+            0.0, 0.0, 0.0, 2.0, 1.0,
+        ],
+        5,
+    )
+    .unwrap();
+    let b = Matrix::from_vec(vec![1.0, -2.0, 3.0, 0.5, 4.0], 1).unwrap();
+
+    let x = solve_tridiagonal(&a, &b).unwrap();
+    let expected = LuFactorization::new(&a).unwrap().solve(&b).unwrap();
+    assert!((x - expected).unwrap().norm() < 1e-4);
+
+    let mut dense = a.clone();
+    dense.set(0, 4, 1.0);
+    assert!(matches!(
+        solve_tridiagonal(&dense, &b),
+        Err(MatrixError::NotTridiagonal)
+    ));
+}
+
+#[test]
+fn matrix_pow() {
+    let a = Matrix::from_vec(vec![1.0, 2.0, -1.0, 3.0], 2).unwrap();
+
+    assert_eq!(
+        a.pow(0).unwrap().elems_raw(),
+        Matrix::identity(2).elems_raw()
+    );
+    assert_eq!(a.pow(1).unwrap().elems_raw(), a.elems_raw());
+
+    let cube = ((&a * &a).unwrap() * &a).unwrap();
+    assert_eq!(a.pow(3).unwrap().elems_raw(), cube.elems_raw());
+
+    let rect = Matrix::<f32>::new(3, 2);
+    assert!(matches!(rect.pow(2), Err(MatrixError::NotSquare)));
+}
+
+#[test]
+fn dot_and_outer() {
+    let u = Matrix::from_vec(vec![1.0, 2.0, 3.0], 1).unwrap();
+    let v = Matrix::from_vec(vec![4.0, -5.0, 6.0], 1).unwrap();
+    assert_eq!(u.dot(&v).unwrap(), 12.0);
+    assert!(u.dot(&u.transpose()).is_err());
+
+    let outer = u.outer(&v);
+    assert_eq!((outer.width(), outer.height()), (3, 3));
+    assert_eq!(*outer.get(1, 2), 12.0);
+    assert_eq!(outer.elems_raw(), (&u * v.transpose()).unwrap().elems_raw());
+
+    let z = Matrix::from_vec(vec![Complex::new(1.0, 2.0), Complex::new(-3.0, 0.5)], 1).unwrap();
+    let self_dot = z.dot(&z).unwrap();
+    assert_eq!(self_dot.im, 0.0);
+    assert!(self_dot.re >= 0.0);
+    assert!((self_dot.re - z.norm_squared()).abs() < 1e-6);
+}
+
+#[test]
+fn frobenius_and_spectral_norms_differ() {
+    let a = Matrix::from_vec(vec![1.0, 1.0, 0.0, 1.0], 2).unwrap();
+
+    assert_eq!(a.norm(), a.frobenius_norm());
+    assert!((a.frobenius_norm() - 3f32.sqrt()).abs() < 1e-6);
+    let golden_ratio = (1.0 + 5f32.sqrt()) / 2.0;
+    assert!((a.spectral_norm().unwrap() - golden_ratio).abs() < 1e-4);
+
+    let q = Matrix::from_vec(vec![0.6, -0.8, 0.8, 0.6], 2).unwrap();
+    assert!((q.spectral_norm().unwrap() - 1.0).abs() < 1e-4);
+    assert_eq!(Matrix::<f32>::new(2, 2).spectral_norm().unwrap(), 0.0);
+}
+
+#[test]
+fn spectral_norm_with_close_singular_values() {
+    let a = Matrix::from_vec(vec![1.0, 0.0, 0.0, 0.95], 2).unwrap();
+    assert!((a.spectral_norm().unwrap() - 1.0).abs() < 1e-4);
+
+    // close top singular values slow the iteration down, but the estimate
+    // still lands between them
+    for second in [0.99, 0.999, 0.9999, 1.0] {
+        let a = Matrix::from_vec(vec![1.0, 0.0, 0.0, second], 2).unwrap();
+        let norm = a.spectral_norm().unwrap();
+        assert!(norm >= second - 1e-6 && norm <= 1.0 + 1e-6, "{}", second);
+    }
+    let a = Matrix::from_vec(vec![3.0, 0.0, 0.0, 0.0, 2.99, 0.0, 0.0, 0.0, 1.0], 3).unwrap();
+    let norm = a.spectral_norm().unwrap();
+    assert!((2.99..=3.0 + 1e-6).contains(&norm));
+
+    assert!(matches!(
+        a.spectral_norm_with_options(1, 0.0),
+        Err(MatrixError::DidNotConverge { iterations: 1, .. })
+    ));
+}
+
+#[test]
+fn inverse_power_iteration_targets_nearest_eigenvalue() {
+    let a = Matrix::from_vec(vec![2.0, 1.0, 0.0, 1.0, 3.0, 1.0, 0.0, 1.0, 4.0], 3).unwrap();
+    // eigenvalues are 3 and 3 ± √3
+    let (lambda, v) = inverse_power_iteration(&a, 1.0, 100, 1e-6).unwrap();
+    assert!((lambda - (3.0 - 3f32.sqrt())).abs() < 1e-4);
+    assert!((a.mul_vec(&v).unwrap() - &v * lambda).unwrap().norm() < 1e-3);
+
+    let (lambda, _) = inverse_power_iteration(&a, 3.2, 100, 1e-6).unwrap();
+    assert!((lambda - 3.0).abs() < 1e-4);
+
+    let diag = Matrix::from_vec(vec![1.0, 0.0, 0.0, 2.0], 2).unwrap();
+    assert!(matches!(
+        inverse_power_iteration(&diag, 2.0, 100, 1e-6),
+        Err(MatrixError::NotRegular)
+    ));
+}
+
+#[test]
+fn gershgorin_discs_of_dominant_matrix_exclude_zero() {
+    let a = Matrix::from_vec(vec![5.0, 1.0, -2.0, 1.0, -6.0, 3.0, 0.5, 0.5, 4.0], 3).unwrap();
+    let discs = a.gershgorin_discs();
+
+    assert_eq!(discs, vec![(5.0, 3.0), (-6.0, 4.0), (4.0, 1.0)]);
+    for (center, radius) in discs {
+        assert!(center.abs() > radius);
+    }
+
+    let z = Matrix::from_vec(
+        vec![
+            Complex::new(0.0, 4.0),
+            Complex::new(3.0, 4.0),
+            Complex::new(1.0, 0.0),
+            Complex::new(-2.0, 0.0),
+        ],
+        2,
+    )
+    .unwrap();
+    let discs = z.gershgorin_discs();
+    assert_eq!(discs[0], (Complex::new(0.0, 4.0), 5.0));
+    assert_eq!(discs[1], (Complex::new(-2.0, 0.0), 1.0));
+}
+
+#[test]
+fn conjugate_gradient_matches_lu() {
+    let a = Matrix::from_vec(vec![4.0, 1.0, 0.0, 1.0, 3.0, -1.0, 0.0, -1.0, 2.0], 3).unwrap();
+    let b = Matrix::from_vec(vec![1.0, 2.0, 3.0], 1).unwrap();
+
+    let (x, residual) = conjugate_gradient(&a, &b, 3, 1e-6).unwrap();
+    assert!(residual <= 1e-6);
+    assert!((&b - a.mul_vec(&x).unwrap()).unwrap().norm() < 1e-5);
+    let expected = LuFactorization::new(&a).unwrap().solve(&b).unwrap();
+    assert!((x - expected).unwrap().norm() < 1e-4);
+}
+
+#[test]
+fn jacobi_and_gauss_seidel_match_lu() {
+    let a = Matrix::from_vec(vec![10.0, -1.0, 2.0, -1.0, 11.0, -1.0, 2.0, -1.0, 10.0], 3).unwrap();
+    let b = Matrix::from_vec(vec![6.0, 25.0, -11.0], 1).unwrap();
+    let expected = LuFactorization::new(&a).unwrap().solve(&b).unwrap();
+
+    let x = jacobi(&a, &b, 100, 1e-5).unwrap();
+    assert!((x - &expected).unwrap().norm() < 1e-4);
+    let x = gauss_seidel(&a, &b, 100, 1e-5).unwrap();
+    assert!((x - &expected).unwrap().norm() < 1e-4);
+
+    let mut singular = a.clone();
+    singular.set(1, 1, 0.0);
+    assert!(matches!(
+        jacobi(&singular, &b, 100, 1e-5),
+        Err(MatrixError::NotRegular)
+    ));
+    assert!(matches!(
+        gauss_seidel(&singular, &b, 100, 1e-5),
+        Err(MatrixError::NotRegular)
+    ));
+}
+
+#[test]
+fn triangular_solves() {
+    let u = Matrix::from_vec(vec![2.0, 1.0, -1.0, 0.0, 3.0, 2.0, 0.0, 0.0, 4.0], 3).unwrap();
+    let b = Matrix::from_vec(vec![4.0, 15.0, 12.0], 1).unwrap();
+    let x = u.solve_upper_triangular(&b).unwrap();
+    assert_eq!(x.elems_raw(), &[2.0, 3.0, 3.0]);
+
+    let l = u.transpose();
+    let b = Matrix::from_vec(vec![4.0, 11.0, 12.0], 1).unwrap();
+    let x = l.solve_lower_triangular(&b).unwrap();
+    assert_eq!(x.elems_raw(), &[2.0, 3.0, 2.0]);
+
+    let mut singular = u.clone();
+    singular.set(1, 1, 0.0);
+    assert!(matches!(
+        singular.solve_upper_triangular(&b),
+        Err(MatrixError::NotRegular)
+    ));
+}
+
+#[test]
+fn householder_skips_zero_columns() {
+    // nothing to reflect in the first column, dividing by its norm gave NaNs
+    let a = Matrix::from_vec(vec![0.0, 1.0, 2.0, 0.0, 3.0, 4.0, 0.0, 5.0, 7.0], 3).unwrap();
+    let (q, r) = qr_householder(&a).unwrap();
+    assert!(r.elems_raw().iter().all(|x| x.is_finite()));
+    let qtq = (q.transpose() * &q).unwrap();
+    assert!((qtq - Matrix::identity(3)).unwrap().norm() < 1e-5);
+    assert!(((q * r).unwrap() - a).unwrap().norm() < 1e-5);
+}
+
+#[test]
+fn symmetry_predicates() {
+    let sym = Matrix::from_vec(vec![1.0, 2.0, 3.0, 2.0, 4.0, 5.0, 3.0, 5.0, 6.0], 3).unwrap();
+    assert!(sym.is_symmetric(0.0));
+    assert!(sym.is_hermitian(0.0));
+
+    let mut nearly = sym.clone();
+    nearly.set(0, 2, 3.0005);
+    assert!(!nearly.is_symmetric(0.0));
+    assert!(nearly.is_symmetric(1e-3));
+    assert!(!Matrix::<f32>::new(3, 2).is_symmetric(1.0));
+
+    let herm = Matrix::from_vec(
+        vec![
+            Complex::new(2.0, 0.0),
+            Complex::new(1.0, -3.0),
+            Complex::new(1.0, 3.0),
+            Complex::new(5.0, 0.0),
+        ],
+        2,
+    )
+    .unwrap();
+    assert!(herm.is_hermitian(1e-6));
+    assert!(!herm.is_symmetric(1e-6));
+
+    let mut not_herm = herm.clone();
+    not_herm.set(0, 0, Complex::new(2.0, 1.0));
+    assert!(!not_herm.is_hermitian(1e-6));
+}
+
+fn reconstruct_svd(u: &Matrix<f32>, sigma: &[f32], vt: &Matrix<f32>) -> Matrix<f32> {
+    let mut s = Matrix::new(sigma.len(), sigma.len());
+    for (i, x) in sigma.iter().enumerate() {
+        s.set(i, i, *x);
+    }
+    ((u * s).unwrap() * vt).unwrap()
+}
+
+#[test]
+fn svd_of_diagonal_and_2x2() {
+    let d = Matrix::from_vec(vec![3.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 2.0], 3).unwrap();
+    let (u, sigma, vt) = svd(&d).unwrap();
+    assert_eq!(sigma, vec![3.0, 2.0, 1.0]);
+    assert!((reconstruct_svd(&u, &sigma, &vt) - &d).unwrap().norm() < 1e-5);
+
+    let a = Matrix::from_vec(vec![3.0, 0.0, 4.0, 5.0], 2).unwrap();
+    let (u, sigma, vt) = svd(&a).unwrap();
+    assert!((sigma[0] - 45f32.sqrt()).abs() < 1e-5);
+    assert!((sigma[1] - 5f32.sqrt()).abs() < 1e-5);
+    assert!((reconstruct_svd(&u, &sigma, &vt) - &a).unwrap().norm() < 1e-5);
+
+    let wide = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 3).unwrap();
+    let (u, sigma, vt) = svd(&wide).unwrap();
+    assert_eq!(
+        (u.width(), u.height(), vt.width(), vt.height()),
+        (2, 2, 3, 2)
+    );
+    assert!((reconstruct_svd(&u, &sigma, &vt) - &wide).unwrap().norm() < 1e-4);
+}
+
+#[test]
+fn pseudoinverse_of_regular_matrix_is_inverse() {
+    let a = Matrix::from_vec(vec![4.0, 7.0, 2.0, 6.0], 2).unwrap();
+    let inverse = Matrix::from_vec(vec![0.6, -0.7, -0.2, 0.4], 2).unwrap();
+    assert!((a.pseudoinverse(1e-6) - inverse).unwrap().norm() < 1e-5);
+
+    // rank 1, rectangular: A A⁺ A == A still holds
+    let r = Matrix::from_vec(vec![1.0, 2.0, 2.0, 4.0, 3.0, 6.0], 2).unwrap();
+    let p = r.pseudoinverse(1e-4);
+    assert_eq!((p.width(), p.height()), (3, 2));
+    let rpr = ((&r * &p).unwrap() * &r).unwrap();
+    assert!((rpr - &r).unwrap().norm() < 1e-4);
+}
+
+#[test]
+fn matrix_exponential() {
+    let zero = Matrix::<f32>::new(3, 3);
+    assert_eq!(
+        zero.exp().unwrap().elems_raw(),
+        Matrix::identity(3).elems_raw()
+    );
+
+    let d = Matrix::from_vec(vec![2.0, 0.0, 0.0, -1.5], 2).unwrap();
+    let expected = Matrix::from_vec(vec![2f32.exp(), 0.0, 0.0, (-1.5f32).exp()], 2).unwrap();
+    assert!((d.exp().unwrap() - expected).unwrap().norm() < 1e-5);
+
+    let t = 3.0f32;
+    let generator = Matrix::from_vec(vec![0.0, -t, t, 0.0], 2).unwrap();
+    let rotation = Matrix::from_vec(vec![t.cos(), -t.sin(), t.sin(), t.cos()], 2).unwrap();
+    assert!((generator.exp().unwrap() - rotation).unwrap().norm() < 1e-5);
+
+    assert!(matches!(
+        Matrix::<f32>::new(2, 3).exp(),
+        Err(MatrixError::NotSquare)
+    ));
+}
+
+fn sorted_by_re_im(mut values: Vec<Complex>) -> Vec<Complex> {
+    values.sort_by(|a, b| a.re.total_cmp(&b.re).then(a.im.total_cmp(&b.im)));
+    values
+}
+
+#[test]
+fn shifted_qr_eigenvalues() {
+    // the 5x5 second-difference matrix has eigenvalues 2 - 2cos(kπ/6)
+    let mut a = Matrix::new(5, 5);
+    for i in 0..5 {
+        a.set(i, i, 2.0);
+        if i > 0 {
+            a.set(i, i - 1, -1.0);
+            a.set(i - 1, i, -1.0);
+        }
+    }
+    let values = sorted_by_re_im(eigenvalues(&a, 50, 1e-6).unwrap());
+    for (k, lambda) in values.iter().enumerate() {
+        let expected = 2.0 - 2.0 * ((k + 1) as f32 * std::f32::consts::PI / 6.0).cos();
+        assert!((lambda.re - expected).abs() < 1e-4);
+        assert_eq!(lambda.im, 0.0);
+    }
+
+    // companion matrix of (x - 1)(x² + 1)
+    let c = Matrix::from_vec(vec![0.0, 0.0, 1.0, 1.0, 0.0, -1.0, 0.0, 1.0, 1.0], 3).unwrap();
+    let values = sorted_by_re_im(eigenvalues(&c, 100, 1e-6).unwrap());
+    let expected = [
+        Complex::new(0.0, -1.0),
+        Complex::new(0.0, 1.0),
+        Complex::new(1.0, 0.0),
+    ];
+    for (lambda, expected) in values.iter().zip(expected) {
+        assert!((lambda - expected).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn eigenvectors_satisfy_definition() {
+    let a = Matrix::from_vec(vec![4.0, 1.0, 0.0, 1.0, 3.0, 1.0, 0.0, 1.0, 2.0], 3).unwrap();
+    let values = eigenvalues(&a, 100, 1e-6).unwrap();
+    let vectors = eigenvectors(&a, &values).unwrap();
+    assert_eq!(vectors.len(), 3);
+    for (lambda, v) in values.iter().zip(&vectors) {
+        let residual = (a.mul_vec(v).unwrap() - v * lambda.re).unwrap();
+        assert!(residual.norm() < 1e-3);
+        assert!((v.norm() - 1.0).abs() < 1e-5);
+    }
+
+    // the complex pair of a rotation is skipped
+    let c = Matrix::from_vec(vec![0.0, 0.0, 1.0, 1.0, 0.0, -1.0, 0.0, 1.0, 1.0], 3).unwrap();
+    let values = eigenvalues(&c, 100, 1e-6).unwrap();
+    let vectors = eigenvectors(&c, &values).unwrap();
+    assert_eq!(vectors.len(), 1);
+    let residual = (c.mul_vec(&vectors[0]).unwrap() - &vectors[0] * 1.0).unwrap();
+    assert!(residual.norm() < 1e-3);
+}
+
+#[test]
+fn from_rows_and_columns() {
+    let rows = vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]];
+    let a = Matrix::from_rows(&rows).unwrap();
+    assert_eq!((a.width(), a.height()), (3, 2));
+    assert_eq!(a.elems_raw(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+    let b = Matrix::from_columns(&rows).unwrap();
+    assert_eq!((b.width(), b.height()), (2, 3));
+    assert_eq!(b.elems_raw(), &[1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+
+    let ragged = vec![vec![1.0, 2.0], vec![3.0]];
+    assert!(matches!(
+        Matrix::<f32>::from_rows(&ragged),
+        Err(MatrixError::SizeMismatch)
+    ));
+    assert!(matches!(
+        Matrix::<f32>::from_columns(&ragged),
+        Err(MatrixError::SizeMismatch)
+    ));
+}
+
+#[test]
+fn row_and_column_iterators() {
+    let a = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 2).unwrap();
+
+    let rows: Vec<_> = a.rows().collect();
+    assert_eq!(rows.len(), 3);
+    for (i, row) in rows.iter().enumerate() {
+        assert_eq!((row.width(), row.height()), (2, 1));
+        assert_eq!(row.elems_raw(), &[*a.get(i, 0), *a.get(i, 1)]);
+    }
+
+    let columns: Vec<_> = a.columns().collect();
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns[0].elems_raw(), &[1.0, 3.0, 5.0]);
+    assert_eq!(columns[1].elems_raw(), &[2.0, 4.0, 6.0]);
+}
+
+#[test]
+fn map_and_zip_with() {
+    let a = Matrix::from_vec(vec![1.0, -2.0, 3.0, -4.0], 2).unwrap();
+    let b = Matrix::from_vec(vec![0.0, 5.0, 3.5, -5.0], 2).unwrap();
+
+    let doubled = a.map(|x| x * 2.0);
+    assert_eq!(doubled.elems_raw(), &[2.0, -4.0, 6.0, -8.0]);
+
+    let max = a.zip_with(&b, |x, y| x.max(*y)).unwrap();
+    assert_eq!(max.elems_raw(), &[1.0, 5.0, 3.5, -4.0]);
+
+    let c = Matrix::from_vec(vec![1.0, 2.0], 2).unwrap();
+    assert!(matches!(
+        a.zip_with(&c, |x, y| x + y),
+        Err(MatrixError::SizeMismatch)
+    ));
+}
+
+#[test]
+fn hadamard_product() {
+    let a = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0], 2).unwrap();
+    let b = Matrix::from_vec(vec![5.0, 6.0, 7.0, 8.0], 2).unwrap();
+
+    let h = a.hadamard(&b).unwrap();
+    assert_eq!(h.elems_raw(), &[5.0, 12.0, 21.0, 32.0]);
+
+    let product = (&a * &b).unwrap();
+    assert_ne!(h.elems_raw(), product.elems_raw());
+
+    let c = Matrix::from_vec(vec![1.0, 2.0, 3.0], 3).unwrap();
+    assert!(matches!(a.hadamard(&c), Err(MatrixError::SizeMismatch)));
+}
+
+#[test]
+fn longint_norm_beyond_u32() {
+    let big = Matrix::from_vec(vec![LongInt::from(1u64 << 40), LongInt::from(3)], 2).unwrap();
+    let small =
+        Matrix::from_vec(vec![LongInt::from(u32::MAX as u64), LongInt::from(3)], 2).unwrap();
+    let (big_norm, small_norm) = (big.norm(), small.norm());
+    assert!(big_norm.is_finite());
+    assert!(big_norm > small_norm);
+    assert!((big_norm / (1u64 << 40) as f32 - 1.0).abs() < 1e-6);
+
+    // 2^70 squared overflows f32 and saturates
+    let huge = LongInt::from(1u64 << 35) * LongInt::from(1u64 << 35);
+    assert_eq!(huge.norm_squared(), f32::INFINITY);
+    let x: f32 = LongInt::from(-12345).into();
+    assert_eq!(x, -12345.0);
+}
+
+#[test]
+fn banded_matrix() {
+    let n = 6;
+    let mut dense = Matrix::new(n, n);
+    for i in 0..n {
+        dense.set(i, i, 5.0 + i as f32);
+        if i > 0 {
+            dense.set(i, i - 1, -1.0);
+        }
+        if i + 1 < n {
+            dense.set(i, i + 1, 2.0);
+        }
+        if i + 2 < n {
+            dense.set(i, i + 2, 0.5);
+        }
+    }
+
+    let banded = BandedMatrix::from_matrix(&dense, 1, 2).unwrap();
+    assert_eq!(banded.get(4, 0), 0.0);
+    assert_eq!(banded.get(0, 2), 0.5);
+    assert_eq!(banded.to_matrix().elems_raw(), dense.elems_raw());
+    assert!(matches!(
+        BandedMatrix::from_matrix(&dense, 1, 1),
+        Err(MatrixError::NotBanded)
+    ));
+
+    let b = Matrix::from_vec(vec![1.0, -2.0, 3.0, 0.0, 4.0, 1.5], 1).unwrap();
+    let banded_x = banded.solve(&b).unwrap();
+    let dense_x = LuFactorization::new(&dense).unwrap().solve(&b).unwrap();
+    assert!((banded_x - dense_x).unwrap().norm() < 1e-5);
+}
+
+#[test]
+fn sparse_identity_spmv() {
+    let n = 1000;
+    let mut eye = SparseMatrix::new(n, n);
+    for i in 0..n {
+        eye.push(i, i, 1.0);
+    }
+
+    let v = Matrix::from_vec((0..n).map(|i| i as f32 - 3.5).collect(), 1).unwrap();
+    let res = eye.mul_vec(&v).unwrap();
+    assert_eq!(res.elems_raw(), v.elems_raw());
+
+    let mut small = SparseMatrix::new(3, 2);
+    small.push(0, 2, 4.0);
+    small.push(1, 0, -1.0);
+    small.push(0, 2, 1.0);
+    assert_eq!(
+        small.to_matrix().elems_raw(),
+        &[0.0, 0.0, 5.0, -1.0, 0.0, 0.0]
+    );
+    assert!(matches!(small.mul_vec(&v), Err(MatrixError::SizeMismatch)));
+}
+
+#[test]
+fn householder_r_is_exactly_triangular() {
+    let a = Matrix::from_vec(
+        vec![
+            4.0, 1.0, -2.0, 2.0, 1.0, 2.0, 0.0, 1.0, -2.0, 0.0, 3.0, -2.0, 2.0, 1.0, -2.0, -1.0,
+        ],
+        4,
+    )
+    .unwrap();
+    let (q, r) = qr_householder(&a).unwrap();
+    for i in 1..4 {
+        for j in 0..i {
+            assert_eq!(*r.get(i, j), 0.0);
+        }
+    }
+    assert!(((q * r).unwrap() - a).unwrap().norm() < 1e-5);
+}
+
+#[test]
+fn householder_with_zero_on_the_diagonal() {
+    let a = Matrix::from_vec(vec![0.0, 1.0, 1.0, 0.0], 2).unwrap();
+    let (q, r) = qr_householder(&a).unwrap();
+    assert_eq!(*r.get(1, 0), 0.0);
+    assert!((r.get(0, 0).abs() - 1.0).abs() < 1e-6);
+    assert!(q.orthogonality_error() < 1e-6);
+    assert!(((q * r).unwrap() - a).unwrap().norm() < 1e-6);
+}
+
+#[test]
+fn complex_parse_round_trip() {
+    let z = Complex::new(3.5, -0.25);
+    assert_eq!(z.to_parseable_string(), "3.5-0.25i");
+    assert_eq!(z.to_parseable_string().parse::<Complex>().unwrap(), z);
+    assert_eq!("3.5 - 0.25i".parse::<Complex>().unwrap(), z);
+
+    let w = Complex::new(0.1, 1.0 / 3.0);
+    assert_eq!(w.to_parseable_string().parse::<Complex>().unwrap(), w);
+    assert_eq!("2".parse::<Complex>().unwrap(), Complex::new(2.0, 0.0));
+    assert_eq!("-i".parse::<Complex>().unwrap(), Complex::new(0.0, -1.0));
+    assert_eq!(
+        "1e-3+2e+2i".parse::<Complex>().unwrap(),
+        Complex::new(1e-3, 200.0)
+    );
+    assert!("1+2".parse::<Complex>().is_err());
+    assert!("".parse::<Complex>().is_err());
+}
+
+#[test]
+fn longint_from_str() {
+    let x: LongInt = "123456789012345".parse().unwrap();
+    assert_eq!(x, LongInt::from(123456789012345i64));
+    assert_eq!(x.to_decimal(), "123456789012345");
+
+    let big = "-98765432109876543210123456789";
+    let y: LongInt = big.parse().unwrap();
+    assert_eq!(y.to_decimal(), big);
+    assert_eq!("+42".parse::<LongInt>().unwrap(), LongInt::from(42));
+    assert_eq!("-0".parse::<LongInt>().unwrap(), LongInt::from(0));
+
+    assert_eq!("".parse::<LongInt>(), Err(ParseLongIntError::NoDigits));
+    assert_eq!("-".parse::<LongInt>(), Err(ParseLongIntError::NoDigits));
+    assert_eq!(
+        "+-5".parse::<LongInt>(),
+        Err(ParseLongIntError::InvalidDigit {
+            index: 1,
+            byte: b'-'
+        })
+    );
+    assert_eq!(
+        "12a4".parse::<LongInt>(),
+        Err(ParseLongIntError::InvalidDigit {
+            index: 2,
+            byte: b'a'
+        })
+    );
+    assert_eq!(
+        "12a4".parse::<LongInt>().unwrap_err().to_string(),
+        "invalid digit 'a' at byte 2"
+    );
+}
+
+#[test]
+fn longint_rem_euclid() {
+    let r = |a: i64, m: i64| LongInt::from(a).rem_euclid(&LongInt::from(m));
+    assert_eq!(r(7, 3), LongInt::from(1));
+    assert_eq!(r(-7, 3), LongInt::from(2));
+    assert_eq!(r(7, -3), LongInt::from(1));
+    assert_eq!(r(-7, -3), LongInt::from(2));
+    assert_eq!(r(-6, 3), LongInt::from(0));
+    assert_eq!(r(-1, 256), LongInt::from(255));
+    assert_eq!(r(-70000, 65537), LongInt::from(61074));
+
+    let big: LongInt = "-123456789012345678901234567890".parse().unwrap();
+    let m = LongInt::from(1_000_000_007);
+    // 123456789012345678901234567890 mod (1e9 + 7) = 197434842
+    assert_eq!(big.rem_euclid(&m), LongInt::from(1_000_000_007 - 197434842));
+}
+
+#[test]
+fn exact_fraction_determinant() {
+    let frac = |num: i64, den: i64| Fraction::new(LongInt::from(den), LongInt::from(num));
+
+    let a = Matrix::from_vec(vec![frac(1, 2), frac(1, 3), frac(1, 4), frac(1, 5)], 2).unwrap();
+    // 1/10 - 1/12
+    assert_eq!(a.determinant().unwrap(), frac(1, 60));
+
+    // zero leading pivot forces a row swap
+    let b = from_f32_mat::<Fraction<LongInt>>(
+        &Matrix::from_vec(vec![0.0, 2.0, 1.0, 3.0, 1.0, -1.0, 1.0, 4.0, 2.0], 3).unwrap(),
+    );
+    assert_eq!(b.determinant().unwrap(), frac(-3, 1));
+
+    let c =
+        from_f32_mat::<Fraction<LongInt>>(&Matrix::from_vec(vec![0.5, 1.0, 0.25, 3.0], 2).unwrap());
+    assert_eq!(c.determinant().unwrap(), frac(5, 4));
+
+    let singular =
+        from_f32_mat::<Fraction<LongInt>>(&Matrix::from_vec(vec![1.0, 2.0, 2.0, 4.0], 2).unwrap());
+    assert_eq!(singular.determinant().unwrap(), frac(0, 1));
+    assert_eq!(Fraction::<LongInt>::from(-0.75), frac(-3, 4));
+}
+
+#[test]
+fn gram_schmidt_repeated_column() {
+    // the third column repeats the first
+    let a = Matrix::from_vec(vec![1.0, 2.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0], 3).unwrap();
+    let (q, r, dependent) = qr_gram_schmidt_masked(&a, 0.1, 1e-6).unwrap();
+
+    assert_eq!(dependent, vec![false, false, true]);
+    assert!(q.elems_raw().iter().all(|x| x.is_finite()));
+    assert!(r.elems_raw().iter().all(|x| x.is_finite()));
+    for i in 0..3 {
+        assert_eq!(*q.get(i, 2), 0.0);
+    }
+    assert!(((q * r).unwrap() - a).unwrap().norm() < 1e-5);
+}
+
+#[test]
+fn orthogonality_error_of_q() {
+    // 6x6 Hilbert matrix, condition number around 1.5e7
+    let n = 6;
+    let mut hilbert = Matrix::new(n, n);
+    for i in 0..n {
+        for j in 0..n {
+            hilbert.set(i, j, 1.0 / (i + j + 1) as f32);
+        }
+    }
+
+    let (householder_q, _) = qr_householder(&hilbert).unwrap();
+    // an epsilon this large disables reorthogonalization: plain classical Gram-Schmidt
+    let (gram_schmidt_q, _) = qr_gram_schmidt(&hilbert, f32::MAX).unwrap();
+
+    let householder_error = householder_q.orthogonality_error();
+    let gram_schmidt_error = gram_schmidt_q.orthogonality_error();
+    assert!(householder_error < 1e-5);
+    assert!(gram_schmidt_error > 100.0 * householder_error);
+
+    let complex_q = Matrix::from_vec(
+        vec![
+            Complex::new(0.0, 1.0),
+            0.0.into(),
+            0.0.into(),
+            Complex::new(0.6, 0.8),
+        ],
+        2,
+    )
+    .unwrap();
+    assert!(complex_q.orthogonality_error() < 1e-6);
+}
+
+#[test]
+fn read_several_matrices() {
+    let file = "A = ...\n[1 2 3;\n4 5 6];\n\
+                B = complex([1 0;\n0 1],[0 2;\n-2 0]);\n\
+                C = ...\n[7;\n8;\n9];\n";
+    let mats = read_mats(&mut file.as_bytes()).unwrap();
+    assert_eq!(mats.len(), 3);
+
+    let a = mats[0].unwrap_left();
+    assert_eq!((a.width(), a.height()), (3, 2));
+    let b = mats[1].unwrap_right();
+    assert_eq!((b.width(), b.height()), (2, 2));
+    assert_eq!(*b.get(1, 0), Complex::new(0.0, -2.0));
+    let c = mats[2].unwrap_left();
+    assert_eq!((c.width(), c.height()), (1, 3));
+    assert_eq!(c.elems_raw(), &[7.0, 8.0, 9.0]);
+
+    assert!(matches!(&mats[0], Either::Left(_)));
+    assert!(read_mats(&mut "".as_bytes()).unwrap().is_empty());
+}
+
+#[test]
+fn left_and_right_scaling_agree() {
+    let a = Matrix::from_vec(vec![1.0, -2.0, 0.5, 4.0, 3.0, -1.5], 3).unwrap();
+    let right = &a * 2.5;
+    assert_eq!((2.5 * &a).elems_raw(), right.elems_raw());
+    assert_eq!(a.scale(&2.5).elems_raw(), right.elems_raw());
+    assert_eq!((2.5 * a).elems_raw(), right.elems_raw());
+
+    let z = Matrix::from_vec(vec![Complex::new(1.0, 1.0), Complex::new(0.0, -2.0)], 2).unwrap();
+    let i = Complex::new(0.0, 1.0);
+    assert_eq!(z.scale(&i).elems_raw(), (&z * i).elems_raw());
+    assert_eq!(
+        (2.0 * &z).elems_raw(),
+        (&z * Complex::from(2.0)).elems_raw()
+    );
+}
+
+#[test]
+fn matrix_negation() {
+    let a = Matrix::from_vec(vec![1.0, -2.0, 0.0, 4.5], 2).unwrap();
+    assert_eq!((-&a).elems_raw(), (&a * -1.0).elems_raw());
+    assert_eq!((-a.clone()).elems_raw(), (a * -1.0).elems_raw());
+
+    let z = Matrix::from_vec(vec![Complex::new(1.0, -1.0), Complex::new(-3.0, 0.5)], 1).unwrap();
+    assert_eq!((-&z).elems_raw(), (&z * Complex::from(-1.0)).elems_raw());
+    assert_eq!(
+        (-z.clone()).elems_raw(),
+        (z * Complex::from(-1.0)).elems_raw()
+    );
+}
+
+#[test]
+fn longint_f32_mul_div() {
+    assert_eq!(LongInt::from(100) / 4.0, LongInt::from(25));
+    assert_eq!(LongInt::from(100) / 0.5, LongInt::from(200));
+    assert_eq!(LongInt::from(-9) / 2.0, LongInt::from(-4));
+    assert_eq!(LongInt::from(100) * 4.0, LongInt::from(400));
+    assert_eq!(LongInt::from(10) * 0.5, LongInt::from(5));
+    assert_eq!(LongInt::from(-7) * 1.5, LongInt::from(-10));
+
+    // the whole finite range converts exactly, subnormals included
+    let max: LongInt = "340282346638528859811704183484516925440".parse().unwrap();
+    assert_eq!(LongInt::from(1) * f32::MAX, max);
+    assert_eq!(LongInt::from(1) / f32::MAX, LongInt::from(0));
+    let pow2_149: LongInt = "713623846352979940529142984724747568191373312"
+        .parse()
+        .unwrap();
+    assert_eq!(LongInt::from(1) / f32::from_bits(1), pow2_149);
+    assert_eq!(LongInt::from(3) * -0.0, LongInt::from(0));
+}
+
+#[test]
+#[should_panic(expected = "cannot convert NaN to a fraction")]
+fn longint_mul_by_nan_panics() {
+    let _ = LongInt::from(1) * f32::NAN;
+}
+
+#[test]
+#[should_panic(expected = "cannot convert inf to a fraction")]
+fn longint_div_by_infinity_panics() {
+    let _ = LongInt::from(1) / f32::INFINITY;
+}
+
+#[test]
+#[should_panic(expected = "Division by zero")]
+fn longint_div_by_zero_f32_panics() {
+    let _ = LongInt::from(1) / 0.0;
+}
+
+#[test]
+fn iteration_cap_is_reported() {
+    let a = Matrix::from_vec(vec![4.0, 1.0, 0.0, 1.0, 3.0, 1.0, 0.0, 1.0, 2.0], 3).unwrap();
+    let b = Matrix::from_vec(vec![1.0, 2.0, 3.0], 1).unwrap();
+
+    match jacobi(&a, &b, 1, 1e-6) {
+        Err(MatrixError::DidNotConverge {
+            iterations,
+            residual,
+        }) => {
+            assert_eq!(iterations, 1);
+            assert!(residual > 1e-6);
+        }
+        other => panic!("expected DidNotConverge, got {:?}", other),
+    }
+    assert!(matches!(
+        gauss_seidel(&a, &b, 1, 1e-6),
+        Err(MatrixError::DidNotConverge { iterations: 1, .. })
+    ));
+    assert!(matches!(
+        eigenvalues(&a, 1, 1e-6),
+        Err(MatrixError::DidNotConverge { iterations: 1, .. })
+    ));
+    assert!(matches!(
+        inverse_power_iteration(&a, 0.0, 1, 1e-6),
+        Err(MatrixError::DidNotConverge { iterations: 1, .. })
+    ));
+
+    // with enough iterations the same calls succeed
+    assert!(jacobi(&a, &b, 200, 1e-5).is_ok());
+    assert!(eigenvalues(&a, 100, 1e-6).is_ok());
+
+    let err = MatrixError::DidNotConverge {
+        iterations: 3,
+        residual: 0.5,
+    };
+    assert_eq!(
+        err.to_string(),
+        "DidNotConverge after 3 iterations, residual 0.5"
+    );
+}
+
+#[test]
+fn zeros_ones_and_identity_check() {
+    let z = Matrix::<f32>::zeros(3, 2);
+    assert_eq!((z.width(), z.height()), (3, 2));
+    assert!(z.elems_raw().iter().all(|x| *x == 0.0));
+
+    let o = Matrix::<Complex>::ones(2, 3);
+    assert_eq!((o.width(), o.height()), (2, 3));
+    assert!(o.elems_raw().iter().all(|x| *x == Complex::new(1.0, 0.0)));
+
+    assert!(Matrix::<f32>::identity(4).is_identity(0.0));
+    let mut almost = Matrix::<f32>::identity(3);
+    almost.set(0, 2, 1e-4);
+    assert!(almost.is_identity(1e-3));
+    assert!(!almost.is_identity(1e-5));
+    assert!(!Matrix::<f32>::ones(2, 2).is_identity(0.5));
+    assert!(!Matrix::<f32>::new(3, 2).is_identity(1.0));
+
+    let (q, _) = qr_householder(&Matrix::from_vec(vec![2.0, 1.0, 1.0, 3.0], 2).unwrap()).unwrap();
+    assert!((q.hermetian_transpose() * &q).unwrap().is_identity(1e-5));
+}
+
+#[test]
+fn longint_matrix_product_is_exact() {
+    let a_vals: Vec<i64> = vec![1 << 40, -3, 7, -(1 << 39) + 5, 12345678901, 0];
+    let b_vals: Vec<i64> = vec![-(1 << 38), 2, 999999999, -1, 5, 1 << 35];
+    let a = Matrix::from_vec(a_vals.iter().map(|&x| LongInt::from(x)).collect(), 3).unwrap();
+    let b = Matrix::from_vec(b_vals.iter().map(|&x| LongInt::from(x)).collect(), 2).unwrap();
+
+    let c = (&a * &b).unwrap();
+    assert_eq!((c.width(), c.height()), (2, 2));
+    for i in 0..2 {
+        for j in 0..2 {
+            let expected: i128 = (0..3)
+                .map(|k| a_vals[i * 3 + k] as i128 * b_vals[k * 2 + j] as i128)
+                .sum();
+            assert_eq!(c.get(i, j).to_decimal(), expected.to_string());
+        }
+    }
+
+    let mut acc = LongInt::from(-5);
+    acc.mul_add_assign(&LongInt::from(2), &LongInt::from(3));
+    assert_eq!(acc, LongInt::from(1));
+    acc.mul_add_assign(&LongInt::from(-255), &LongInt::from(255));
+    assert_eq!(acc, LongInt::from(1 - 255 * 255));
+}
+
+#[test]
+fn make_lu_with_row_swap() {
+    let dir = TempDir::new("make_lu");
+
+    // a zero in the corner needs a row swap before anything else
+    let a = Matrix::from_vec(vec![0.0, 2.0, 1.0, 1.0, 1.0, 1.0, 2.0, 1.0, 0.0], 3).unwrap();
+    let b = Matrix::from_vec(vec![3.0, 3.0, 3.0], 1).unwrap();
+    write_mat_f32(&a, &dir.join("Amat1.m")).unwrap();
+    write_mat_f32(&b, &dir.join("bvec1.m")).unwrap();
+
+    let (perm, l, u) = lu_decomposition(&a).unwrap();
+    assert_ne!(perm, vec![0, 1, 2]);
+    assert!(lu_reconstruction_error(&a, &perm, &l, &u).unwrap() < 1e-6);
+    assert_eq!(
+        permutation_from_matrix(&permutation_matrix(&perm)).unwrap(),
+        perm
+    );
+
+    make_lu(&dir, 1).unwrap();
+    let p = read_mat(&mut std::fs::File::open(dir.join("Pmat1.m")).unwrap())
+        .unwrap()
+        .0;
+    assert_eq!(permutation_from_matrix(p.unwrap_left()).unwrap(), perm);
+
+    lu_gauss(&dir, 1).unwrap();
+    let x = read_mat(&mut std::fs::File::open(dir.join("xvec1.m")).unwrap())
+        .unwrap()
+        .0;
+    assert!((a.mul_vec(x.unwrap_left()).unwrap() - b).unwrap().norm() < 1e-5);
+}
+
+#[test]
+fn solve_for_identity_gives_inverse() {
+    let a = Matrix::from_vec(vec![0.0, 2.0, 1.0, 1.0, 1.0, 1.0, 2.0, 1.0, 0.0], 3).unwrap();
+    let eye = Matrix::identity(3);
+
+    let x = LuFactorization::new(&a).unwrap().solve(&eye).unwrap();
+    assert_eq!((x.width(), x.height()), (3, 3));
+    assert!((&a * &x).unwrap().is_identity(1e-5));
+
+    let (q, r) = qr_householder(&a).unwrap();
+    let y = gauss_from_qr(&q, &r, &eye).unwrap();
+    assert!((&a * &y).unwrap().is_identity(1e-5));
+    assert!((x - y).unwrap().norm() < 1e-5);
+
+    // a single column still goes through the same path
+    let b = Matrix::from_vec(vec![3.0, 3.0, 3.0], 1).unwrap();
+    let x = gauss_from_qr(&q, &r, &b).unwrap();
+    assert!((a.mul_vec(&x).unwrap() - b).unwrap().norm() < 1e-5);
+}
+
+#[test]
+fn header_with_epsilon() {
+    let file = "Method=3 Epsilon=0.001\nA = ...\n[1 2;\n3 4];\n";
+    let (mat, header) = read_mat(&mut file.as_bytes()).unwrap();
+    assert!(matches!(header.method, Some(QRMethod::GramSchmidt)));
+    assert_eq!(header.epsilon, 0.001);
+
+    let mat = mat.unwrap_left();
+    let (q, r) = qr_gram_schmidt(mat, header.epsilon).unwrap();
+    assert!(((&q * &r).unwrap() - mat).unwrap().norm() < 1e-5);
+
+    let (_, header) = read_mat(&mut "Method=1\nA = ...\n[1];\n".as_bytes()).unwrap();
+    assert!(matches!(header.method, Some(QRMethod::Householder)));
+    assert_eq!(header.epsilon, DEFAULT_GRAM_SCHMIDT_EPSILON);
+
+    let (_, header) = read_mat(&mut "Epsilon=1e-4\nA = ...\n[1];\n".as_bytes()).unwrap();
+    assert!(header.method.is_none());
+    assert_eq!(header.epsilon, 1e-4);
+
+    assert!(matches!(
+        read_mat(&mut "Epsilon=abc\nA = ...\n[1];\n".as_bytes()),
+        Err(MatrixError::InvalidFileFormat)
+    ));
+}
+
+#[test]
+fn characteristic_polynomial_tolerates_noise() {
+    let c = |re: f32, im: f32| Complex::new(re, im);
+    let mut mat = Matrix::from_vec(
+        vec![
+            c(2.0, 0.0),
+            c(1.0, 0.0),
+            c(0.0, 0.0),
+            c(1.0, 0.0),
+            c(3.0, 0.0),
+            c(0.0, 0.0),
+            c(0.0, 0.0),
+            c(0.0, 0.0),
+            c(4.0, 0.0),
+        ],
+        3,
+    )
+    .unwrap();
+    mat.set(0, 2, c(0.0, 0.05));
+    mat.set(2, 0, c(-0.05, 0.0));
+
+    assert!(matches!(
+        characteristic_polynomial(&mat),
+        Err(MatrixError::NotTridiagonal)
+    ));
+
+    let p = characteristic_polynomial_with_tol(&mat, 0.01).unwrap();
+    assert_eq!(p.degree(), 3);
+    assert!((p.get(0) - c(20.0, 0.0)).norm() < 1e-5);
+}
+
+#[test]
+fn stack_matrices() {
+    let a = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0], 3).unwrap();
+    let b = Matrix::from_vec(vec![10.0, 11.0, 12.0], 1).unwrap();
+    let ab = a.hstack(&b).unwrap();
+    assert_eq!((ab.width(), ab.height()), (4, 3));
+    assert_eq!(
+        ab.elems_raw(),
+        &[1.0, 2.0, 3.0, 10.0, 4.0, 5.0, 6.0, 11.0, 7.0, 8.0, 9.0, 12.0]
+    );
+    assert!(matches!(b.vstack(&a), Err(MatrixError::SizeMismatch)));
+
+    let c = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0], 2).unwrap();
+    let d = Matrix::from_vec(vec![5.0, 6.0, 7.0, 8.0], 2).unwrap();
+    let cd = c.vstack(&d).unwrap();
+    assert_eq!((cd.width(), cd.height()), (2, 4));
+    assert_eq!(cd.elems_raw(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+    assert!(matches!(cd.hstack(&c), Err(MatrixError::SizeMismatch)));
+}
+
+#[test]
+fn rref_with_free_variable() {
+    // x + 2y + z = 4, 2x + 4y + 3z = 11, -x - 2y + z = 2 has y free
+    let a = Matrix::from_vec(
+        vec![
+            1.0, 2.0, 1.0, 4.0, 2.0, 4.0, 3.0, 11.0, -1.0, -2.0, 1.0, 2.0,
+        ],
+        4,
+    )
+    .unwrap();
+    let (r, pivots) = a.rref(1e-6);
+
+    assert_eq!(pivots, vec![0, 2]);
+    let expected = Matrix::from_vec(
+        vec![1.0, 2.0, 0.0, 1.0, 0.0, 0.0, 1.0, 3.0, 0.0, 0.0, 0.0, 0.0],
+        4,
+    )
+    .unwrap();
+    assert!((r - expected).unwrap().norm() < 1e-5);
+}
+
+#[test]
+fn null_space_of_rank_deficient_matrix() {
+    let a = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0], 3).unwrap();
+    let basis = a.null_space(1e-5);
+
+    assert_eq!(basis.len(), 1);
+    let v = &basis[0];
+    assert_eq!((v.width(), v.height()), (1, 3));
+    assert!(v.norm() > 0.5);
+    assert!(a.mul_vec(v).unwrap().norm() < 1e-4);
+
+    assert!(Matrix::<f32>::identity(3).null_space(1e-5).is_empty());
+}
+
+#[test]
+fn parse_args_reports_bad_argument() {
+    let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let dir = std::env::temp_dir().to_str().unwrap().to_owned();
+
+    assert!(matches!(
+        parse_args(&args(&["task1", "make_lu", &dir, "3"])),
+        Ok((Operation::MakeLu, _, 3))
+    ));
+    assert!(matches!(
+        parse_args(&args(&["task1", "make_lu", &dir])),
+        Err(ArgsError::MissingArguments)
+    ));
+
+    let err = parse_args(&args(&["task1", "make_lx", &dir, "3"])).unwrap_err();
+    assert!(matches!(&err, ArgsError::UnknownOperation(op) if op == "make_lx"));
+    assert!(err.to_string().contains("bench_qr"));
+
+    assert!(matches!(
+        parse_args(&args(&["task1", "make_lu", "/no/such/dir", "3"])),
+        Err(ArgsError::BadDirectory(d)) if d == "/no/such/dir"
+    ));
+    assert!(matches!(
+        parse_args(&args(&["task1", "make_lu", &dir, "three"])),
+        Err(ArgsError::BadTask(t)) if t == "three"
+    ));
+}
+
+#[test]
+fn write_eigen_file() {
+    let dir = TempDir::new("eigen");
+    let path = dir.join("eigen.m");
+    let values = [
+        Complex::new(2.0, 0.0),
+        Complex::new(1.0, 1.0),
+        Complex::new(1.0, -1.0),
+    ];
+    let v = Matrix::from_vec(vec![1.0, 0.0, 0.0], 1).unwrap();
+    write_eigen(&values, &[v], &path).unwrap();
+
+    let s = std::fs::read_to_string(&path).unwrap();
+    assert!(s.starts_with("lambda = complex([2;\n1;\n1],[0;\n1;\n-1]);"));
+    assert!(s.contains("V = ...\n[1;\n0;\n0];"));
+
+    let mats = read_mats(&mut s.as_bytes()).unwrap();
+    assert_eq!(mats.len(), 2);
+    assert_eq!(mats[0].unwrap_right().elems_raw(), &values);
+    assert_eq!(mats[1].unwrap_left().elems_raw(), &[1.0, 0.0, 0.0]);
+
+    write_eigen(&[Complex::new(3.0, 0.0)], &[], &path).unwrap();
+    let s = std::fs::read_to_string(&path).unwrap();
+    assert!(s.starts_with("lambda = ...\n[3];"));
+    assert!(s.contains("V = ...\n[];"));
+}
+
+#[test]
+fn empty_matrices() {
+    assert!(matches!(
+        Matrix::<f32>::from_vec(vec![1.0], 0),
+        Err(MatrixError::SizeMismatch)
+    ));
+    let empty = Matrix::<f32>::from_vec(vec![], 0).unwrap();
+    assert_eq!((empty.width(), empty.height()), (0, 0));
+    assert_eq!(empty.norm(), 0.0);
+    assert_eq!(format!("{empty}"), "[ ]");
+    assert!(empty.is_identity(0.0));
+
+    let product = (&empty * &empty).unwrap();
+    assert_eq!((product.width(), product.height()), (0, 0));
+
+    // 2x0 times 0x3 is the 2x3 zero matrix
+    let a = Matrix::<f32>::new(0, 2);
+    let b = Matrix::<f32>::new(3, 0);
+    let ab = (&a * &b).unwrap();
+    assert_eq!((ab.width(), ab.height()), (3, 2));
+    assert_eq!(ab.norm(), 0.0);
+    let ba = &b * &a;
+    assert!(matches!(ba, Err(MatrixError::SizeMismatch)));
+
+    let t = b.transpose();
+    assert_eq!((t.width(), t.height()), (0, 3));
+    let sum = (&a + &a).unwrap();
+    assert_eq!((sum.width(), sum.height()), (0, 2));
+
+    let exact = Matrix::<Fraction<LongInt>>::new(0, 0);
+    assert_eq!(exact.determinant().unwrap(), Fraction::from(1.0));
+}
+
+#[test]
+fn as_square_rejects_rectangular() {
+    let rect = Matrix::<f32>::new(3, 2);
+    assert!(matches!(rect.as_square(), Err(MatrixError::NotSquare)));
+    assert!(matches!(rect.pow(2), Err(MatrixError::NotSquare)));
+    assert!(matches!(
+        lu_decomposition(&rect),
+        Err(MatrixError::NotSquare)
+    ));
+
+    let square = Matrix::<f32>::identity(3);
+    assert!(std::ptr::eq(square.as_square().unwrap(), &square));
+}
+
+#[test]
+fn hermetian_transpose_conjugates() {
+    let c = |re: f32, im: f32| Complex::new(re, im);
+    // 2 rows, 3 columns
+    let a = Matrix::from_vec(
+        vec![
+            c(1.0, 2.0),
+            c(3.0, -4.0),
+            c(0.0, 1.0),
+            c(5.0, 0.0),
+            c(-6.0, 7.0),
+            c(8.0, -9.0),
+        ],
+        3,
+    )
+    .unwrap();
+    let ah = a.hermetian_transpose();
+
+    assert_eq!((ah.width(), ah.height()), (2, 3));
+    for i in 0..2 {
+        for j in 0..3 {
+            let z = a.get(i, j);
+            assert_eq!(*ah.get(j, i), Complex::new(z.re, -z.im));
+            assert_eq!(*ah.get(j, i), NumNonRef::conjugate(z));
+        }
+    }
+    assert_eq!(ah.hermetian_transpose().elems_raw(), a.elems_raw());
+
+    let r = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 3).unwrap();
+    assert_eq!(
+        r.hermetian_transpose().elems_raw(),
+        r.transpose().elems_raw()
+    );
+}
+
+#[test]
+fn gauss_eliminate_matches_lu_gauss() {
+    let dir = TempDir::new("gauss");
+
+    // zero pivots in the corner and after the first step
+    let a = Matrix::from_vec(
+        vec![
+            0.0, 1.0, 2.0, 1.0, 1.0, 1.0, 1.0, 0.0, 2.0, 2.0, 1.0, 3.0, 1.0, 0.0, 4.0, 2.0,
+        ],
+        4,
+    )
+    .unwrap();
+    let b = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0], 1).unwrap();
+    write_mat_f32(&a, &dir.join("Amat1.m")).unwrap();
+    write_mat_f32(&b, &dir.join("bvec1.m")).unwrap();
+
+    lu_gauss(&dir, 1).unwrap();
+    let expected = read_mat(&mut std::fs::File::open(dir.join("xvec1.m")).unwrap())
+        .unwrap()
+        .0;
+    let x = gauss_eliminate(&a, &b).unwrap();
+    assert!((&x - expected.unwrap_left()).unwrap().norm() < 1e-5);
+    assert!((a.mul_vec(&x).unwrap() - &b).unwrap().norm() < 1e-5);
+
+    // several right-hand sides at once
+    let x = gauss_eliminate(&a, &Matrix::identity(4)).unwrap();
+    assert!((&a * &x).unwrap().is_identity(1e-5));
+
+    let singular = Matrix::from_vec(vec![1.0, 2.0, 2.0, 4.0], 2).unwrap();
+    assert!(matches!(
+        gauss_eliminate(&singular, &Matrix::new(1, 2)),
+        Err(MatrixError::NotRegular)
+    ));
+    assert!(matches!(
+        gauss_eliminate(&a, &Matrix::new(1, 3)),
+        Err(MatrixError::SizeMismatch)
+    ));
+}
+
+#[test]
+fn characteristic_polynomial_digit_cap() {
+    for (s, bound) in [("0", 1), ("7", 1), ("255", 3), ("-1000000", 7)] {
+        let x: LongInt = s.parse().unwrap();
+        let digits = x.decimal_digits();
+        assert!(digits == bound || digits == bound + 1, "{s}: {digits}");
+    }
+
+    let mut mat = Matrix::<f32>::new(8, 8);
+    for i in 0..8 {
+        mat.set(i, i, 1000.0);
+        if i + 1 < 8 {
+            mat.set(i, i + 1, 999.0);
+            mat.set(i + 1, i, 999.0);
+        }
+    }
+    let mat = from_f32_mat::<LongInt>(&mat);
+
+    assert!(matches!(
+        characteristic_polynomial_max_digits(&mat, 10),
+        Err(MatrixError::TooManyDigits { max_digits: 10 })
+    ));
+    let capped = characteristic_polynomial_max_digits(&mat, 100).unwrap();
+    let plain = characteristic_polynomial(&mat).unwrap();
+    assert_eq!(format!("{capped}"), format!("{plain}"));
+}
+
+#[test]
+fn decomposition_reports() {
+    let a = Matrix::from_vec(vec![4.0, 1.0, 0.0, 1.0, 4.0, 1.0, 0.0, 1.0, 4.0], 3).unwrap();
+
+    let report = lu_report(&a).unwrap();
+    assert!(report.reconstruction_error < 1e-5);
+    assert_eq!(report.perm, vec![0, 1, 2]);
+    assert!(((&report.l * &report.u).unwrap() - &a).unwrap().norm() < 1e-5);
+
+    let report = qr_report(&a, qr_householder).unwrap();
+    assert!(report.reconstruction_error < 1e-5);
+    assert!(report.orthogonality_error < 1e-5);
+}
+
+#[test]
+fn complex_lu_pivots_by_magnitude() {
+    let c = |re: f32, im: f32| Complex::new(re, im);
+    // a tiny leading entry: eliminating with it blows everything else up
+    let a = Matrix::from_vec(
+        vec![
+            c(0.0, 1e-9),
+            c(1.0, 1.0),
+            c(1.0, 0.0),
+            c(1.0, 0.0),
+            c(1.0, -1.0),
+            c(2.0, 0.0),
+            c(0.0, 1.0),
+            c(2.0, 0.0),
+            c(1.0, 1.0),
+        ],
+        3,
+    )
+    .unwrap();
+
+    // Doolittle without any row swaps, for comparison
+    let n = 3;
+    let mut l = Matrix::<Complex>::identity(n);
+    let mut u = Matrix::<Complex>::new(n, n);
+    for i in 0..n {
+        for j in i..n {
+            let mut sum = *a.get(i, j);
+            for k in 0..i {
+                sum = sum - *l.get(i, k) * *u.get(k, j);
+            }
+            u.set(i, j, sum);
+        }
+        for j in i + 1..n {
+            let mut sum = *a.get(j, i);
+            for k in 0..i {
+                sum = sum - *l.get(j, k) * *u.get(k, i);
+            }
+            l.set(j, i, sum / *u.get(i, i));
+        }
+    }
+    let unpivoted = lu_reconstruction_error(&a, &[0, 1, 2], &l, &u).unwrap();
+
+    let (perm, l, u) = lu_decomposition(&a).unwrap();
+    let pivoted = lu_reconstruction_error(&a, &perm, &l, &u).unwrap();
+
+    assert_ne!(perm[0], 0);
+    assert!(pivoted < 1e-5, "{pivoted}");
+    assert!(pivoted * 1000.0 < unpivoted, "{pivoted} vs {unpivoted}");
+}
+
+#[test]
+fn structured_matrices() {
+    let v = Matrix::vandermonde(&[2.0, -1.0, 3.0], 3);
+    assert_eq!((v.width(), v.height()), (4, 3));
+    assert_eq!(
+        v.elems_raw(),
+        &[1.0, 2.0, 4.0, 8.0, 1.0, -1.0, 1.0, -1.0, 1.0, 3.0, 9.0, 27.0]
+    );
+
+    let t = Matrix::toeplitz(&[1.0, 5.0, 6.0], &[1.0, 2.0, 3.0, 4.0]).unwrap();
+    assert_eq!((t.width(), t.height()), (4, 3));
+    assert_eq!(
+        t.elems_raw(),
+        &[1.0, 2.0, 3.0, 4.0, 5.0, 1.0, 2.0, 3.0, 6.0, 5.0, 1.0, 2.0]
+    );
+    assert!(matches!(
+        Matrix::toeplitz(&[1.0, 2.0], &[0.0, 3.0]),
+        Err(MatrixError::ToeplitzCornerMismatch)
+    ));
+    let empty = Matrix::<f32>::toeplitz(&[], &[1.0, 2.0]).unwrap();
+    assert_eq!((empty.width(), empty.height()), (2, 0));
+}
+
+#[test]
+fn interpolate_parabola() {
+    let p = interpolate(&[(-1.0, 1.0), (2.0, 4.0), (3.0, 9.0)]).unwrap();
+    assert_eq!(p.degree(), 2);
+    for (power, expected) in [(0, 0.0), (1, 0.0), (2, 1.0)] {
+        assert!((p.get(power) - expected).abs() < 1e-5, "{}", p.get(power));
+    }
+
+    assert!(matches!(
+        interpolate(&[(1.0, 2.0), (0.0, 0.0), (1.0, 3.0)]),
+        Err(MatrixError::NotRegular)
+    ));
+}
+
+#[test]
+fn characteristic_polynomial_residuals() {
+    let a = Matrix::from_vec(vec![2.0, 1.0, 0.0, 1.0, 2.0, 1.0, 0.0, 1.0, 2.0], 3).unwrap();
+    let p = characteristic_polynomial(&a).unwrap();
+    let s = 2f32.sqrt();
+
+    assert_eq!(p.eval(&2.0), 0.0);
+    for r in p.residuals(&[2.0 - s, 2.0, 2.0 + s]) {
+        assert!(r < 1e-5, "{r}");
+    }
+    assert!(p.residuals(&[0.0])[0] > 1.0);
+
+    let dir = TempDir::new("find_poly");
+    write_mat_f32(&a, &dir.join("Amat1.m")).unwrap();
+    let roots = Matrix::from_vec(vec![2.0 - s, 2.0, 2.0 + s], 1).unwrap();
+    write_mat_f32(&roots, &dir.join("evec1.m")).unwrap();
+    let residuals = find_poly(&dir, 1).unwrap().unwrap();
+    assert_eq!(residuals.len(), 3);
+    for r in residuals {
+        assert!(r < 1e-5, "{r}");
+    }
+
+    let roots = Matrix::from_vec(
+        roots
+            .elems_raw()
+            .iter()
+            .map(|&x| Complex::new(x, 0.0))
+            .collect(),
+        1,
+    )
+    .unwrap();
+    write_mat_complex(&roots, &dir.join("evec1.m")).unwrap();
+    let residuals = find_poly(&dir, 1).unwrap().unwrap();
+    assert_eq!(residuals.len(), 3);
+    for r in residuals {
+        assert!(r < 1e-5, "{r}");
+    }
+
+    // off by one from the middle root
+    let off = Matrix::from_vec(vec![3.0], 1).unwrap();
+    write_mat_f32(&off, &dir.join("evec1.m")).unwrap();
+    assert!(find_poly(&dir, 1).unwrap().unwrap()[0] > 0.5);
+
+    std::fs::remove_file(dir.join("evec1.m")).unwrap();
+    assert_eq!(find_poly(&dir, 1).unwrap(), None);
+}
+
+#[test]
+fn fill_and_shift_diagonal() {
+    let mut a = Matrix::<f32>::new(2, 3);
+    a.fill(7.0);
+    assert_eq!(a.elems_raw(), &[7.0; 6]);
+
+    let mut a = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0], 3).unwrap();
+    let expected = (&a - Matrix::identity(3) * 2.5).unwrap();
+    a.shift_diagonal(&2.5);
+    assert_eq!(a.elems_raw(), expected.elems_raw());
+
+    a.apply_diagonal(|i, x| x + i as f32);
+    assert_eq!(
+        a.elems_raw(),
+        &[-1.5, 2.0, 3.0, 4.0, 3.5, 6.0, 7.0, 8.0, 8.5]
+    );
+}
+
+#[test]
+fn solve_reports_residual() {
+    let dir = TempDir::new("solve");
+
+    let a = Matrix::from_vec(vec![4.0, 1.0, 0.0, 1.0, 4.0, 1.0, 0.0, 1.0, 4.0], 3).unwrap();
+    let b = Matrix::from_vec(vec![1.0, 2.0, 3.0], 1).unwrap();
+    write_mat_f32(&a, &dir.join("Amat1.m")).unwrap();
+    write_mat_f32(&b, &dir.join("bvec1.m")).unwrap();
+
+    for report in [solve_lu(&dir, 1).unwrap(), solve_qr(&dir, 1).unwrap()] {
+        assert!(report.residual < 1e-4, "{}", report.residual);
+        assert_eq!(report.x_path, dir.join("xvec1.m"));
+        let x = read_mat(&mut std::fs::File::open(&report.x_path).unwrap())
+            .unwrap()
+            .0;
+        assert!((a.mul_vec(x.unwrap_left()).unwrap() - &b).unwrap().norm() < 1e-4);
+    }
+}
+
+#[test]
+fn read_empty_or_malformed_file() {
+    for s in [
+        "",
+        "   \n\t\n",
+        "Method=1\n",
+        "A = ...\n1 2;\n3 4];",
+        "A = ...\n[1 2;\n3",
+    ] {
+        assert!(
+            matches!(
+                read_mat(&mut s.as_bytes()),
+                Err(MatrixError::InvalidFileFormat)
+            ),
+            "{s:?}"
+        );
+    }
+    assert!(read_mats(&mut "  \n".as_bytes()).unwrap().is_empty());
+}
+
+#[test]
+fn lu_pivot_threshold() {
+    // the first pivot is 2, the best candidate below it is 3
+    let a = Matrix::from_vec(vec![2.0, 1.0, 1.0, 3.0, 2.0, 1.0, 1.0, 1.0, 4.0], 3).unwrap();
+
+    let (perm, l, u) = lu_decomposition_with_options(&a, 1.0, 0.0).unwrap();
+    assert_eq!(perm[0], 1);
+    assert!(lu_reconstruction_error(&a, &perm, &l, &u).unwrap() < 1e-5);
+    assert_eq!(perm, lu_decomposition(&a).unwrap().0);
+
+    let (perm, l, u) = lu_decomposition_with_options(&a, 2.0, 0.0).unwrap();
+    assert_eq!(perm, vec![0, 1, 2]);
+    assert!(lu_reconstruction_error(&a, &perm, &l, &u).unwrap() < 1e-5);
+
+    // a zero pivot has to go no matter the threshold
+    let b = Matrix::from_vec(vec![0.0, 1.0, 1.0, 1.0], 2).unwrap();
+    let (perm, _, _) = lu_decomposition_with_options(&b, 1e6, 0.0).unwrap();
+    assert_eq!(perm, vec![1, 0]);
+}
+
+#[test]
+fn row_and_column_norms() {
+    let c = |re: f32, im: f32| Complex::new(re, im);
+    let a = Matrix::from_vec(
+        vec![
+            c(1.0, 2.0),
+            c(-3.0, 0.0),
+            c(0.5, -1.0),
+            c(4.0, 4.0),
+            c(0.0, 0.0),
+            c(2.0, -7.0),
+        ],
+        3,
+    )
+    .unwrap();
+
+    for i in 0..a.height() {
+        assert!((a.row_norm(i) - a.row(i).norm()).abs() < 1e-6);
+    }
+    for j in 0..a.width() {
+        assert!((a.col_norm(j) - a.column(j).norm()).abs() < 1e-6);
+    }
+    assert_eq!(a.row_norm(0), 15.25f32.sqrt());
+}
+
+#[test]
+fn givens_with_zero_sub_column() {
+    // the first column is zero, so the first rotations have a = b = 0
+    let a = Matrix::from_vec(vec![0.0, 1.0, 2.0, 0.0, 3.0, 4.0, 0.0, 5.0, 7.0], 3).unwrap();
+    let (q, r) = qr_givens(&a).unwrap();
+
+    assert!(q
+        .elems_raw()
+        .iter()
+        .chain(r.elems_raw())
+        .all(|x| !x.is_nan()));
+    assert!(q.orthogonality_error() < 1e-5);
+    assert!(((&q * &r).unwrap() - &a).unwrap().norm() < 1e-5);
+}
+
+#[test]
+fn ragged_rows() {
+    let file = "A = ...\n[1 2;\n3];";
+    let Err(err) = read_mat(&mut file.as_bytes()) else {
+        panic!("ragged rows were accepted");
+    };
+    assert!(matches!(
+        err,
+        MatrixError::RaggedRow {
+            row: 1,
+            expected: 2,
+            found: 1
+        }
+    ));
+    assert!(err.to_string().contains("row 1"));
+
+    let (mat, _) = read_mat_padded(&mut file.as_bytes()).unwrap();
+    assert_eq!(mat.unwrap_left().elems_raw(), &[1.0, 2.0, 3.0, 0.0]);
+}
+
+#[test]
+fn roots_of_exact_polynomial() {
+    let int = |x: i32| LongInt::from(x);
+    // (x - 1)(x - 2)(x - 3)
+    let p = Polynome::from_coefs(&[int(-6), int(11), int(-6), int(1)]);
+    let p = p.to_f32();
+    assert_eq!(p.get(1), 11.0);
+
+    let mut roots: Vec<_> = p
+        .roots(500, 1e-6)
+        .unwrap()
+        .into_iter()
+        .map(|z| z.re)
+        .collect();
+    roots.sort_by(f32::total_cmp);
+    for (root, expected) in roots.iter().zip([1.0, 2.0, 3.0]) {
+        assert!((root - expected).abs() < 1e-4, "{root}");
+    }
+
+    // x² + 1 has no real roots
+    let p = Polynome::from_coefs(&[int(1), int(0), int(1)]).to_f32();
+    let roots = p.roots(500, 1e-6).unwrap();
+    assert_eq!(roots.len(), 2);
+    for z in roots {
+        assert!(z.re.abs() < 1e-4 && (z.im.abs() - 1.0).abs() < 1e-4, "{z}");
+    }
+
+    // eigenvalues of [2 1; 1 2] from its exact characteristic polynomial
+    let a = from_f32_mat::<LongInt>(&Matrix::from_vec(vec![2.0, 1.0, 1.0, 2.0], 2).unwrap());
+    let p = characteristic_polynomial(&a).unwrap().to_f32();
+    let mut roots: Vec<_> = p
+        .roots(500, 1e-6)
+        .unwrap()
+        .into_iter()
+        .map(|z| z.re)
+        .collect();
+    roots.sort_by(f32::total_cmp);
+    assert!((roots[0] - 1.0).abs() < 1e-4 && (roots[1] - 3.0).abs() < 1e-4);
+}
+
+#[test]
+fn transpose_in_place_matches_transpose() {
+    let a = Matrix::from_vec((0..16).map(|x| x as f32).collect(), 4).unwrap();
+    let mut b = a.clone();
+    b.transpose_in_place().unwrap();
+    assert_eq!(b.elems_raw(), a.transpose().elems_raw());
+
+    let mut rect = Matrix::<f32>::new(3, 2);
+    assert!(matches!(
+        rect.transpose_in_place(),
+        Err(MatrixError::NotSquare)
+    ));
+}
+
+#[test]
+fn either_combinators() {
+    let left: Either<i32, &str> = Either::Left(2);
+    let right: Either<i32, &str> = Either::Right("abc");
+
+    assert_eq!(*left.map_left(|x| x * 10).unwrap_left(), 20);
+    assert_eq!(
+        *Either::<i32, &str>::Right("abc")
+            .map_left(|x| x * 10)
+            .unwrap_right(),
+        "abc"
+    );
+
+    assert_eq!(*right.map_right(|s| s.len()).unwrap_right(), 3);
+    assert_eq!(
+        *Either::<i32, &str>::Left(2)
+            .map_right(|s| s.len())
+            .unwrap_left(),
+        2
+    );
+
+    let both = |e: Either<i32, &str>| e.map_both(|x| x as f32 / 2.0, |s| s.to_uppercase());
+    assert_eq!(*both(Either::Left(3)).unwrap_left(), 1.5);
+    assert_eq!(both(Either::Right("abc")).unwrap_right(), "ABC");
+}
+
+#[test]
+fn symmetry_error_of_nearly_symmetric() {
+    let a = Matrix::from_vec(vec![2.0, 1.0, 0.5, 1.001, 3.0, 0.0, 0.5, 0.0, 4.0], 3).unwrap();
+    let err = a.symmetry_error();
+    // the 0.001 shows up at (0, 1) and (1, 0)
+    assert!((err - 0.001 * 2f32.sqrt()).abs() < 1e-5, "{err}");
+    assert_eq!(a.hermitian_error(), err);
+    assert_eq!(((&a + a.transpose()).unwrap()).symmetry_error(), 0.0);
+    assert_eq!(Matrix::<f32>::new(2, 3).symmetry_error(), f32::INFINITY);
+
+    // real symmetric but not Hermitian once the diagonal is imaginary
+    let c = Matrix::from_vec(vec![Complex::new(0.0, 1.0)], 1).unwrap();
+    assert_eq!(c.symmetry_error(), 0.0);
+    assert_eq!(c.hermitian_error(), 2.0);
+}
+
+#[test]
+fn longint_shifts() {
+    let x: LongInt = "123456789012345678901234567890".parse().unwrap();
+    for bits in [0, 1, 3, 7, 8, 9, 17, 64] {
+        let pow = (0..bits).fold(LongInt::from(1), |p, _| p * LongInt::from(2));
+        assert_eq!(&x << bits, &x * &pow, "<< {bits}");
+        assert_eq!(&x >> bits, &x / &pow, ">> {bits}");
+    }
+    assert_eq!(x.clone() << 3, &x * &LongInt::from(8));
+    assert_eq!(x.clone() >> 1, &x / &LongInt::from(2));
+
+    // the magnitude is shifted, the sign stays
+    assert_eq!(LongInt::from(-12) << 2, LongInt::from(-48));
+    assert_eq!(LongInt::from(-13) >> 2, LongInt::from(-3));
+    assert_eq!(LongInt::from(300) >> 100, LongInt::from(0));
+}
+
+#[test]
+fn lu_flags_tiny_pivots() {
+    let a = Matrix::from_vec(vec![1e-20, 1.0, 0.0, 1.0], 2).unwrap();
+    assert!(matches!(lu_decomposition(&a), Err(MatrixError::NotRegular)));
+    assert!(matches!(
+        LuFactorization::new(&a),
+        Err(MatrixError::NotRegular)
+    ));
+
+    // only an exact zero counts without the threshold
+    let (perm, l, u) = lu_decomposition_with_options(&a, 1.0, 0.0).unwrap();
+    assert_eq!(*u.get(0, 0), 1e-20);
+    assert!(lu_reconstruction_error(&a, &perm, &l, &u).unwrap() < 1e-6);
+}
+
+#[test]
+fn lu_singular_threshold_is_relative() {
+    // well conditioned, only tiny: every entry is below SINGULAR_EPSILON
+    let a = Matrix::from_vec(vec![2e-14, 1e-14, 1e-14, 3e-14], 2).unwrap();
+    let (perm, l, u) = lu_decomposition(&a).unwrap();
+    assert!(lu_reconstruction_error(&a, &perm, &l, &u).unwrap() < 1e-20);
+
+    // the tiny pivot of `lu_flags_tiny_pivots` is still caught after scaling up
+    let b = Matrix::from_vec(vec![1e-20, 1.0, 0.0, 1.0], 2).unwrap();
+    assert!(matches!(
+        lu_decomposition(&(&b * 1e10)),
+        Err(MatrixError::NotRegular)
+    ));
+
+    // exact types never round, a tiny pivot is just a small number
+    let tiny: LongInt = "1000000000000000000000000000000".parse().unwrap();
+    let c = Matrix::from_vec(
+        vec![
+            Fraction::new(tiny.clone(), LongInt::from(1)),
+            Fraction::from(1.0),
+            Fraction::from(0.0),
+            Fraction::from(1.0),
+        ],
+        2,
+    )
+    .unwrap();
+    let (_, _, u) = lu_decomposition(&c).unwrap();
+    assert_eq!(u.get(0, 0), &Fraction::new(tiny, LongInt::from(1)));
+}
+
+#[test]
+fn refinement_reduces_residual() {
+    // Hilbert matrix scaled by 2520 so that every entry is an exact integer.
+    let n = 5;
+    let a = Matrix::from_vec(
+        (0..n * n)
+            .map(|k| (2520 / (k / n + k % n + 1)) as f32)
+            .collect(),
+        n,
+    )
+    .unwrap();
+    let b = Matrix::from_vec(
+        (0..n)
+            .map(|i| (0..n).map(|j| a.get(i, j)).sum::<f32>())
+            .collect(),
+        1,
+    )
+    .unwrap();
+
+    let residual = |x: &Matrix<f32>| {
+        (0..n)
+            .map(|i| {
+                let ax: f64 = (0..n)
+                    .map(|j| *a.get(i, j) as f64 * *x.get(j, 0) as f64)
+                    .sum();
+                (*b.get(i, 0) as f64 - ax).powi(2)
+            })
+            .sum::<f64>()
+            .sqrt()
+    };
+
+    let plain = solve_with_refinement(&a, &b, 0).unwrap();
+    let refined = solve_with_refinement(&a, &b, 3).unwrap();
+    let error = |x: &Matrix<f32>| (x - Matrix::ones(1, n)).unwrap().norm();
+    assert!(residual(&refined) < residual(&plain) / 100.0);
+    assert!(error(&refined) < error(&plain) / 100.0);
+}
+
+#[test]
+fn complex_norm_keeps_imaginary_parts() {
+    let magnitudes = [3.0, -4.0, 1.5, 0.25];
+    let real = Matrix::from_vec(magnitudes.to_vec(), 2).unwrap();
+    let imaginary = Matrix::from_vec(
+        magnitudes.iter().map(|&m| Complex::new(0.0, m)).collect(),
+        2,
+    )
+    .unwrap();
+
+    assert!(imaginary.norm() > 0.0);
+    assert_eq!(imaginary.norm_squared(), real.norm_squared());
+    assert_eq!(imaginary.norm(), real.norm());
+}
+
+#[test]
+fn shifted_solve_far_from_spectrum() {
+    let a = Matrix::from_vec(vec![2.0, 1.0, 0.0, 1.0, 3.0, 1.0, 0.0, 1.0, 4.0], 3).unwrap();
+    let original = a.clone();
+    let b = Matrix::from_vec(vec![1.0, 2.0, 3.0], 1).unwrap();
+
+    let mu = 100.0;
+    let x = shifted_solve(&a, mu, &b).unwrap();
+    assert_eq!(a.elems_raw(), original.elems_raw());
+
+    let shifted = (&a - Matrix::identity(3) * mu).unwrap();
+    assert!(((&shifted * &x).unwrap() - &b).unwrap().norm() < 1e-5 * b.norm());
+
+    let singular = Matrix::from_vec(vec![2.0, 0.0, 0.0, 5.0], 2).unwrap();
+    let b = Matrix::from_vec(vec![1.0, 1.0], 1).unwrap();
+    assert!(matches!(
+        shifted_solve(&singular, 5.0, &b),
+        Err(MatrixError::NotRegular)
+    ));
+}
+
+#[test]
+fn compensated_norm_keeps_small_entries() {
+    // 1 + 10⁴·10⁻⁸: every small square is below half an ulp of the running sum
+    let mut elems = vec![1.0];
+    elems.extend(std::iter::repeat_n(1e-4, 10_000));
+    let v = Matrix::from_vec(elems, 1).unwrap();
+    let exact = 1.0001f32.sqrt();
+
+    assert_eq!(v.norm(), 1.0);
+    assert!((v.norm_compensated() - exact).abs() < 1e-6);
+    assert!((v.dot_compensated(&v).unwrap() - 1.0001).abs() < 1e-6);
+}
+
+#[test]
+fn write_then_read_round_trip() {
+    let dir = TempDir::new("round_trip");
+    let path = dir.join("Amat1.m");
+
+    let read_back =
+        |path: &std::path::PathBuf| read_mat(&mut std::fs::File::open(path).unwrap()).unwrap().0;
+
+    let a = Matrix::from_vec(
+        vec![1.0, -2.5, 1e-20, 3.0e7, 0.1, -0.0, f32::INFINITY, 1.0 / 3.0],
+        4,
+    )
+    .unwrap();
+    write_mat_f32(&a, &path).unwrap();
+    let b = read_back(&path);
+    let b = b.unwrap_left();
+    assert_eq!((b.width(), b.height()), (4, 2));
+    assert_eq!(b.elems_raw(), a.elems_raw());
+
+    let z = Matrix::from_vec(
+        vec![
+            Complex::new(1.0, -1.0),
+            Complex::new(0.5, 2.0),
+            Complex::new(-3.0, 0.0),
+        ],
+        1,
+    )
+    .unwrap();
+    write_mat_complex(&z, &path).unwrap();
+    let w = read_back(&path);
+    let w = w.unwrap_right();
+    assert_eq!((w.width(), w.height()), (1, 3));
+    assert_eq!(w.elems_raw(), z.elems_raw());
+
+    write_mat_f32(&Matrix::new(0, 0), &path).unwrap();
+    let e = read_back(&path);
+    assert_eq!(e.unwrap_left().elems_raw().len(), 0);
+}
+
+#[test]
+fn read_mat_skips_comments() {
+    let file = "% generated by hand\n\
+                Method=2 % givens\n\
+                # the system matrix\n\
+                A = ...\n\
+                [1 2; % first row\n\
+                % a whole line of comment\n\
+                3 4];\n";
+    let (mat, header) = read_mat(&mut file.as_bytes()).unwrap();
+    assert!(matches!(header.method, Some(QRMethod::Givens)));
+    assert_eq!(mat.unwrap_left().elems_raw(), &[1.0, 2.0, 3.0, 4.0]);
+
+    let mats = read_mats(&mut "# two blocks\nA = [1];\n% between\nB = [2];\n".as_bytes()).unwrap();
+    assert_eq!(mats.len(), 2);
+}
+
+#[test]
+fn triangular_predicates() {
+    let u = Matrix::from_vec(vec![1.0, 2.0, 3.0, 0.0, 4.0, 5.0, 0.0, 0.0, 6.0], 3).unwrap();
+    assert!(u.is_upper_triangular(0.0));
+    assert!(!u.is_lower_triangular(0.0));
+    assert!(u.transpose().is_lower_triangular(0.0));
+
+    let mut near = u.clone();
+    near.set(2, 0, 1e-6);
+    assert!(!near.is_upper_triangular(0.0));
+    assert!(near.is_upper_triangular(1e-5));
+
+    let a = Matrix::from_vec(vec![2.0, 1.0, 1.0, 4.0, 3.0, 0.0, 6.0, 5.0, 7.0], 3).unwrap();
+    let (_, r) = qr_householder(&a).unwrap();
+    assert!(r.is_upper_triangular(1e-5));
+    let (_, l, u) =
+        lu_decomposition(&Matrix::from_vec(vec![2.0, 1.0, 4.0, 3.0], 2).unwrap()).unwrap();
+    assert!(l.is_lower_triangular(0.0) && u.is_upper_triangular(0.0));
+}
+
+#[test]
+fn reciprocal_diagonal_preconditioner() {
+    let d = Matrix::from_vec(vec![4.0, 0.0, 0.0, 0.0, -0.5, 0.0, 0.0, 0.0, 8.0], 3).unwrap();
+    let inv = d.recip_scaled(&1.0);
+    assert_eq!(
+        inv.elems_raw(),
+        &[0.25, 0.0, 0.0, 0.0, -2.0, 0.0, 0.0, 0.0, 0.125]
+    );
+    assert!((&d * &inv).unwrap().is_identity(0.0));
+
+    let z = Matrix::from_vec(vec![Complex::new(0.0, 2.0), Complex::new(0.0, 0.0)], 1).unwrap();
+    let w = z.recip_scaled(&Complex::new(2.0, 0.0));
+    assert_eq!(
+        w.elems_raw(),
+        &[Complex::new(0.0, -1.0), Complex::new(0.0, 0.0)]
+    );
+}
+
+#[test]
+fn pcg_beats_cg_on_badly_scaled_system() {
+    // D^½·C·D^½ with a well-conditioned tridiagonal C and a diagonal D spread
+    // over four orders of magnitude
+    let n = 20;
+    let scale: Vec<f32> = (0..n)
+        .map(|i| 10f32.powf(4.0 * ((i * 7) % n) as f32 / n as f32))
+        .collect();
+    let mut a = Matrix::new(n, n);
+    for i in 0..n {
+        a.set(i, i, scale[i]);
+        if i + 1 < n {
+            let c = 0.3 * (scale[i] * scale[i + 1]).sqrt();
+            a.set(i, i + 1, c);
+            a.set(i + 1, i, c);
+        }
+    }
+    let b = Matrix::from_vec((0..n).map(|i| scale[i]).collect(), 1).unwrap();
+    let m = jacobi_preconditioner(&a).unwrap();
+    let tol = 1e-3;
+
+    let (x, residual) = pcg(&a, &b, &m, 15, tol).unwrap();
+    assert!(residual <= tol);
+    assert!((&b - a.mul_vec(&x).unwrap()).unwrap().norm() <= 1e-6 * b.norm());
+    assert!(matches!(
+        conjugate_gradient(&a, &b, 15, tol),
+        Err(MatrixError::DidNotConverge { .. })
+    ));
+}
+
+#[test]
+fn bandwidth_of_pentadiagonal_and_dense() {
+    let n = 6;
+    let mut penta: Matrix<f32> = Matrix::new(n, n);
+    for i in 0..n {
+        for j in i.saturating_sub(2)..(i + 3).min(n) {
+            penta.set(i, j, 1.0 + (i + j) as f32);
+        }
+    }
+    assert_eq!(penta.bandwidth(0.0), (2, 2));
+    assert!(!is_tridiagonal(&penta, 0.0));
+
+    let dense: Matrix<f32> = Matrix::ones(n, n);
+    assert_eq!(dense.bandwidth(0.0), (n - 1, n - 1));
+
+    let mut lower: Matrix<f32> = Matrix::identity(n);
+    lower.set(4, 1, 1e-3);
+    assert_eq!(lower.bandwidth(0.0), (3, 0));
+    assert_eq!(lower.bandwidth(1e-2), (0, 0));
+}
+
+#[test]
+fn find_poly_of_complex_matrix() {
+    let i = Complex::new(0.0, 1.0);
+    let one = Complex::new(1.0, 0.0);
+    let zero = Complex::new(0.0, 0.0);
+    let a = Matrix::from_vec(
+        vec![one + i, one, zero, one, one * 2.0, one, zero, one, -i],
+        3,
+    )
+    .unwrap();
+
+    // det(A - λI) = -λ³ + 3λ² + (-1 + i)λ + (1 - 2i)
+    let expected = [
+        Complex::new(1.0, -2.0),
+        Complex::new(-1.0, 1.0),
+        Complex::new(3.0, 0.0),
+        Complex::new(-1.0, 0.0),
+    ];
+    let p = characteristic_polynomial(&a).unwrap();
+    assert_eq!(p.degree(), 3);
+    for (k, c) in expected.iter().enumerate() {
+        assert_eq!(&p.get(k), c);
+    }
+
+    let dir = TempDir::new("find_poly_complex");
+    write_mat_complex(&a, &dir.join("Amat1.m")).unwrap();
+    find_poly(&dir, 1).unwrap();
+
+    let cvec = read_mat(&mut std::fs::File::open(dir.join("cvec1.m")).unwrap())
+        .unwrap()
+        .0;
+    let highest_first: Vec<Complex> = expected.iter().rev().cloned().collect();
+    assert_eq!(cvec.unwrap_right().elems_raw(), &highest_first[..]);
+}
+
+#[test]
+fn longint_zero_and_one_are_identities() {
+    let zero = LongInt::zero();
+    let one = LongInt::one();
+    assert_eq!(zero, LongInt::from(0));
+    assert_eq!(one, LongInt::from(1));
+    assert_eq!(zero.to_string(), "0");
+
+    for x in [
+        LongInt::from(0),
+        LongInt::from(7),
+        LongInt::from(-123456789i64),
+    ] {
+        assert_eq!(&x + &zero, x);
+        assert_eq!(&x * &one, x);
+        assert_eq!(&x * &zero, zero);
+    }
+
+    let eye: Matrix<LongInt> = Matrix::identity(3);
+    let m = from_f32_mat::<LongInt>(
+        &Matrix::from_vec(vec![1.0, -2.0, 3.0, 4.0, 5.0, -6.0, 7.0, 8.0, 9.0], 3).unwrap(),
+    );
+    assert_eq!((&m * &eye).unwrap().elems_raw(), m.elems_raw());
+}
+
+#[test]
+fn newton_polishes_roots() {
+    // x² - 2
+    let p = Polynome::from_coefs(&[-2.0, 0.0, 1.0]);
+    let d = p.derivative();
+    assert_eq!((d.degree(), d.get(0), d.get(1)), (1, 0.0, 2.0));
+
+    let c = Polynome::from_coefs(&[5.0]).derivative();
+    assert_eq!((c.degree(), c.get(0)), (0, 0.0));
+    assert_eq!(c.derivative().degree(), 0);
+
+    let root = p.polish_root(1.0, 20);
+    assert!((root - 2f32.sqrt()).abs() <= f32::EPSILON * 2.0);
+    assert_eq!(p.polish_root(0.0, 20), 0.0);
+
+    // x² + 1, roots ±i
+    let q = Polynome::from_coefs(&[1.0, 0.0, 1.0]);
+    let z = q.polish_complex_root(Complex::new(0.3, 0.8), 20);
+    assert!((z - Complex::new(0.0, 1.0)).abs() < 1e-6);
+}
+
+#[test]
+fn polynomial_division_and_deflation() {
+    // (x - 1)(x - 2)(x - 3) = x³ - 6x² + 11x - 6
+    let p = Polynome::from_coefs(&[-6.0, 11.0, -6.0, 1.0]);
+    let (q, r) = p.div_rem(&Polynome::from_coefs(&[-2.0, 1.0])).unwrap();
+    assert_eq!(
+        (q.degree(), q.get(0), q.get(1), q.get(2)),
+        (2, 3.0, -4.0, 1.0)
+    );
+    assert_eq!(r.get(0), 0.0);
+
+    let (q, r) = p.div_rem(&Polynome::from_coefs(&[1.0, 0.0, 1.0])).unwrap();
+    assert_eq!((q.get(0), q.get(1)), (-6.0, 1.0));
+    assert_eq!((r.get(0), r.get(1)), (0.0, 10.0));
+
+    // constant divisor leaves a zero remainder, not an empty one
+    let x = Polynome::from_coefs(&[0.0, 1.0]);
+    let (q, r) = x.div_rem(&Polynome::from_coefs(&[2.0])).unwrap();
+    assert_eq!((q.degree(), q.get(0), q.get(1)), (1, 0.0, 0.5));
+    assert_eq!((r.degree(), r.get(0)), (0, 0.0));
+
+    // dividend of lower degree than the divisor gives a zero quotient
+    let (q, r) = Polynome::from_coefs(&[1.0])
+        .div_rem(&Polynome::from_coefs(&[1.0, 1.0]))
+        .unwrap();
+    assert_eq!((q.degree(), q.get(0)), (0, 0.0));
+    assert_eq!((r.degree(), r.get(0)), (0, 1.0));
+
+    // the zero polynomial, however many coefficients it has, is not a divisor
+    assert!(p.div_rem(&Polynome::from_coefs(&[0.0, 0.0])).is_none());
+
+    let mut roots = p.all_roots_by_deflation(50, 1e-4);
+    roots.sort_by(f32::total_cmp);
+    assert_eq!(roots.len(), 3);
+    for (root, expected) in roots.iter().zip([1.0, 2.0, 3.0]) {
+        assert!((root - expected).abs() < 1e-4);
+    }
+
+    // x² + 1 has no real roots at all
+    let q = Polynome::from_coefs(&[1.0, 0.0, 1.0]);
+    assert!(q.all_roots_by_deflation(50, 1e-4).is_empty());
+}
+
+#[test]
+fn threshold_masks() {
+    let a = Matrix::from_vec(vec![-1.0, 0.5, 2.0, 0.5, 3.0, f32::NAN], 3).unwrap();
+    let ge = a.ge_mask(0.5);
+    assert_eq!((ge.width(), ge.height()), (3, 2));
+    assert_eq!(ge.elems_raw(), &[0.0, 1.0, 1.0, 1.0, 1.0, 0.0]);
+    assert_eq!(a.le_mask(0.5).elems_raw(), &[1.0, 1.0, 0.0, 1.0, 0.0, 0.0]);
+
+    // masks combine with the elementwise product
+    let kept = a.ge_mask(1.0).hadamard(&a.le_mask(2.5)).unwrap();
+    assert_eq!(kept.elems_raw(), &[0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+}
+
+#[test]
+fn complex_division_by_zero() {
+    let z = Complex::new(1.0, 2.0);
+    let zero = Complex::new(0.0, 0.0);
+
+    let q = z / zero;
+    assert!(q.re.is_nan() && q.im.is_nan());
+
+    assert_eq!(z.checked_div(zero), None);
+    assert_eq!(z.checked_div(Complex::new(1e-30, 0.0)), None);
+    assert_eq!(
+        z.checked_div(Complex::new(0.0, 1.0)),
+        Some(Complex::new(2.0, -1.0))
+    );
+}
+
+#[test]
+fn parallel_multiply_matches_serial() {
+    let n = 200;
+    let a = Matrix::from_vec(
+        (0..n * n)
+            .map(|k| ((k * 37) % 101) as f32 / 7.0 - 5.0)
+            .collect(),
+        n,
+    )
+    .unwrap();
+    let b = Matrix::from_vec(
+        (0..n * n)
+            .map(|k| ((k * 53) % 97) as f32 / 11.0 - 4.0)
+            .collect(),
+        n,
+    )
+    .unwrap();
+
+    let serial = (&a * &b).unwrap();
+    let parallel = a.par_mul(&b).unwrap();
+    assert_eq!(parallel.elems_raw(), serial.elems_raw());
+
+    let tall = Matrix::from_vec((0..n * 3).map(|k| k as f32).collect(), 3).unwrap();
+    let wide = Matrix::from_vec((0..3 * 5).map(|k| k as f32).collect(), 5).unwrap();
+    let p = tall.par_mul(&wide).unwrap();
+    assert_eq!((p.width(), p.height()), (5, n));
+    assert_eq!(p.elems_raw(), (&tall * &wide).unwrap().elems_raw());
+
+    assert!(matches!(a.par_mul(&wide), Err(MatrixError::SizeMismatch)));
+
+    // zero-sized operands keep the shape sequential `Mul` gives them
+    for (lhs, rhs) in [
+        (Matrix::new(3, n), Matrix::new(0, 3)),
+        (Matrix::new(0, 4), Matrix::new(5, 0)),
+        (Matrix::new(3, 0), Matrix::new(2, 3)),
+    ] {
+        let p = lhs.par_mul(&rhs).unwrap();
+        let s = (&lhs * &rhs).unwrap();
+        assert_eq!((p.width(), p.height()), (s.width(), s.height()));
+        assert_eq!(p.elems_raw(), s.elems_raw());
+    }
+}
+
+#[test]
+fn parallel_lu_matches_serial() {
+    let n = 300;
+    let a = Matrix::from_vec(
+        (0..n * n)
+            .map(|k| ((k * 7919) % 1009) as f32 / 100.0 - 5.0)
+            .collect(),
+        n,
+    )
+    .unwrap();
+
+    let (perm, l, u) = lu_decomposition(&a).unwrap();
+    let (par_perm, par_l, par_u) = par_lu_decomposition(&a).unwrap();
+    assert_eq!(par_perm, perm);
+    assert_eq!(par_l.elems_raw(), l.elems_raw());
+    assert_eq!(par_u.elems_raw(), u.elems_raw());
+
+    let error = lu_reconstruction_error(&a, &par_perm, &par_l, &par_u).unwrap();
+    assert!(error / a.norm() < 1e-4);
+
+    let small = Matrix::from_vec(vec![0.0, 1.0, 2.0, 3.0], 2).unwrap();
+    assert_eq!(par_lu_decomposition(&small).unwrap().0, vec![1, 0]);
+}
+
+#[test]
+fn raw_buffer_round_trip() {
+    let a = Matrix::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 3).unwrap();
+    let (elems, width, height) = a.clone().into_raw();
+    assert_eq!((width, height), (3, 2));
+    assert_eq!(elems, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+    let b = Matrix::from_vec(elems, width).unwrap();
+    assert_eq!(b.height(), height);
+    assert_eq!(b.elems_raw(), a.elems_raw());
+
+    let mut c = b;
+    c.as_slice_mut().iter_mut().for_each(|x| *x *= 2.0);
+    assert_eq!(*c.get(1, 2), 12.0);
+}
+
+#[test]
+fn qr_and_lu_determinants_agree() {
+    let swap = Matrix::from_vec(vec![0.0, 1.0, 1.0, 0.0], 2).unwrap();
+    assert!((swap.determinant_via_qr().unwrap() + 1.0).abs() < 1e-6);
+    let diag = Matrix::from_vec(vec![2.0, 0.0, 0.0, 3.0], 2).unwrap();
+    assert!((diag.determinant_via_qr().unwrap() - 6.0).abs() < 1e-5);
+    let singular = Matrix::from_vec(vec![1.0, 2.0, 2.0, 4.0], 2).unwrap();
+    assert!(singular.determinant_via_qr().unwrap().abs() < 1e-5);
+
+    let n = 6;
+    let a = Matrix::from_vec(
+        (0..n * n)
+            .map(|k| ((k * k * 7 + 3 * k) % 23) as f32 / 4.0 - 2.0)
+            .collect(),
+        n,
+    )
+    .unwrap();
+    let (perm, _, u) = lu_decomposition(&a).unwrap();
+    // the sign of a permutation is the parity of its inversions
+    let inversions = (0..n)
+        .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+        .filter(|&(i, j)| perm[i] > perm[j])
+        .count();
+    let lu_det: f32 =
+        (0..n).map(|i| u.get(i, i)).product::<f32>() * if inversions % 2 == 0 { 1.0 } else { -1.0 };
+
+    let qr_det = a.determinant_via_qr().unwrap();
+    assert!(lu_det.abs() > 1.0);
+    assert!((qr_det - lu_det).abs() <= 1e-4 * lu_det.abs());
+}
+
+#[test]
+fn thin_gram_schmidt_qr() {
+    let a = Matrix::from_vec(vec![1.0, 2.0, 0.0, 1.0, 1.0, 0.0, 2.0, 3.0], 2).unwrap();
+    let (q, r) = qr_gram_schmidt(&a, 0.1).unwrap();
+    assert_eq!((q.width(), q.height()), (2, 4));
+    assert_eq!((r.width(), r.height()), (2, 2));
+
+    assert!(q.orthogonality_error() < 1e-5);
+    assert!(r.is_upper_triangular(0.0));
+    assert!(((&q * &r).unwrap() - &a).unwrap().norm() < 1e-5);
+
+    let wide = a.transpose();
+    assert!(matches!(
+        qr_gram_schmidt(&wide, 0.1),
+        Err(MatrixError::SizeMismatch)
+    ));
+}
+
+#[test]
+fn complex_display_precision_and_width() {
+    assert_eq!(format!("{:.2}", Complex::new(1.0, -2.0)), "1.00-2.00i");
+    assert_eq!(format!("{:.1}", Complex::new(-0.25, 3.0)), "-0.2+3.0i");
+    assert_eq!(format!("{:.3}", Complex::new(0.0, -0.5)), "-0.500i");
+    assert_eq!(format!("{:.2}", Complex::new(2.0, 0.0)), "2.00");
+    assert_eq!(format!("{:.1}", Complex::new(0.0, 0.0)), "0.0");
+
+    // unchanged without flags
+    assert_eq!(format!("{}", Complex::new(1.5, -2.0)), "1.5-2i");
+    assert_eq!(format!("{}", Complex::new(0.0, 0.0)), "0");
+
+    assert_eq!(format!("{:>8.1}", Complex::new(1.0, 2.0)), "1.0+2.0i");
+    assert_eq!(format!("{:>10.1}|", Complex::new(1.0, 2.0)), "  1.0+2.0i|");
+    assert_eq!(format!("{:<10.1}|", Complex::new(1.0, 2.0)), "1.0+2.0i  |");
+    assert_eq!(format!("{:*^6}", Complex::new(0.0, 1.0)), "**1i**");
+}
+
+#[test]
+fn real_complex_mismatch_is_an_error() {
+    let dir = TempDir::new("mismatch");
+
+    let a = Matrix::from_vec(vec![2.0, 1.0, 1.0, 3.0], 2).unwrap();
+    let b = Matrix::from_vec(vec![Complex::new(1.0, 1.0), Complex::new(0.0, -1.0)], 1).unwrap();
+    write_mat_f32(&a, &dir.join("Amat1.m")).unwrap();
+    write_mat_complex(&b, &dir.join("bvec1.m")).unwrap();
+
+    let Err(MatrixError::RealComplexMismatch { real, complex }) = solve_lu(&dir, 1) else {
+        panic!("expected a real/complex mismatch");
+    };
+    assert_eq!((real.as_str(), complex.as_str()), ("Amat1.m", "bvec1.m"));
+    assert!(matches!(
+        solve_qr(&dir, 1),
+        Err(MatrixError::RealComplexMismatch { .. })
+    ));
+
+    // factors on disk are checked against each other too
+    write_mat_f32(&a, &dir.join("bvec1.m")).unwrap();
+    write_mat_f32(&a, &dir.join("Qmat1.m")).unwrap();
+    write_mat_complex(&b, &dir.join("Rmat1.m")).unwrap();
+    let Err(err) = solve_qr(&dir, 1) else {
+        panic!("expected a real/complex mismatch");
+    };
+    assert_eq!(
+        err.to_string(),
+        "RealComplexMismatch: Qmat1.m is real but Rmat1.m is complex"
+    );
+}